@@ -15,6 +15,7 @@ import androidx.lifecycle.viewModelScope
 import com.anvlkv.redsiren.core.typegen.Activity
 import com.anvlkv.redsiren.core.typegen.AnimateOperation
 import com.anvlkv.redsiren.core.typegen.AnimateOperationOutput
+import com.anvlkv.redsiren.core.typegen.CaptureOutput
 import com.anvlkv.redsiren.core.typegen.Effect
 import com.anvlkv.redsiren.core.typegen.Event
 import com.anvlkv.redsiren.core.typegen.KeyValueOperation
@@ -27,6 +28,7 @@ import com.anvlkv.redsiren.core.typegen.Requests
 import com.anvlkv.redsiren.core.typegen.ViewModel
 import com.anvlkv.redsiren.ffirs.AuCoreBridge
 import com.anvlkv.redsiren.ffirs.AuReceiver
+import com.anvlkv.redsiren.ffirs.auCapture
 import com.anvlkv.redsiren.ffirs.auNew
 import com.anvlkv.redsiren.ffirs.auReceive
 import com.anvlkv.redsiren.ffirs.auRequest
@@ -52,7 +54,8 @@ open class Core : androidx.lifecycle.ViewModel() {
 
     var view: ViewModel by mutableStateOf(ViewModel.bincodeDeserialize(view()))
     var navigateTo: Activity? by mutableStateOf(null)
-    var animationSender: SendChannel<Long>? by mutableStateOf(null)
+    var animationSenders: Map<Long, SendChannel<Long>> by mutableStateOf(mapOf())
+    var animationPaused: Boolean by mutableStateOf(false)
     var store: DataStore<Preferences>? by mutableStateOf(null)
 
     private val httpClient = HttpClient(CIO)
@@ -163,20 +166,34 @@ open class Core : androidx.lifecycle.ViewModel() {
             }
 
             is Effect.Animate -> {
-                when (effect.value) {
+                when (val op = effect.value) {
                     is AnimateOperation.Start -> {
-                        Log.i("redsiren::android", "starting animation loop")
+                        val id = op.id
+                        Log.i("redsiren::android", "starting animation loop $id")
                         val channel = Channel<Long>(Channel.CONFLATED)
 
-                        animationSender = channel
-                        coroutineScope {
+                        animationSenders[id]?.close()
+                        animationSenders = animationSenders + (id to channel)
+                        viewModelScope.launch {
                             animateStream(channel, request.uuid.toByteArray())
                         }
                     }
                     is AnimateOperation.Stop -> {
-                        Log.i("redsiren::android", "stopping animation loop")
-                        animationSender?.close()
-                        animationSender = null
+                        val id = op.id
+                        Log.i("redsiren::android", "stopping animation loop $id")
+                        animationSenders[id]?.close()
+                        animationSenders = animationSenders - id
+                        if (animationSenders.isEmpty()) {
+                            animationPaused = false
+                        }
+                    }
+                    is AnimateOperation.Pause -> {
+                        Log.i("redsiren::android", "pausing animation loop")
+                        animationPaused = true
+                    }
+                    is AnimateOperation.Resume -> {
+                        Log.i("redsiren::android", "resuming animation loop")
+                        animationPaused = false
                     }
                 }
             }
@@ -209,6 +226,15 @@ open class Core : androidx.lifecycle.ViewModel() {
         Log.i("redsiren::android", "animation stream loop exited")
     }
 
+    private suspend fun captureStream(rec: AuReceiver) {
+        while (true) {
+            val d = auReceive(rec) ?: break
+            update(Event.Capture(CaptureOutput.bincodeDeserialize(d)))
+        }
+
+        Log.i("redsiren::android", "capture stream loop exited")
+    }
+
     private suspend fun playEffect(value: PlayOperation, onData: (ByteArray) -> Job) {
         when (value) {
             is PlayOperation.Permissions -> {
@@ -222,6 +248,11 @@ open class Core : androidx.lifecycle.ViewModel() {
 
             is PlayOperation.InstallAU -> {
                 installAu()
+                capture()?.let { rec ->
+                    viewModelScope.launch {
+                        captureStream(rec)
+                    }
+                }
                 forward(value)?.let {rec ->
                     auReceive(rec)?.let {
                         onData(it).join()
@@ -254,6 +285,12 @@ open class Core : androidx.lifecycle.ViewModel() {
                 return auRequest(it, op.bincodeSerialize())
             }
         }
+
+        fun capture(): AuReceiver? {
+            return auBridge?.let {
+                return auCapture(it)
+            }
+        }
     }
 }
 
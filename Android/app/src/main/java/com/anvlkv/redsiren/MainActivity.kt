@@ -2,10 +2,12 @@ package com.anvlkv.redsiren
 
 import android.animation.TimeAnimator
 import android.content.Context
+import android.content.Intent
 import android.content.res.Resources
 import android.os.Bundle
 import android.util.Log
 import androidx.activity.ComponentActivity
+import androidx.activity.compose.BackHandler
 import androidx.activity.compose.setContent
 import androidx.compose.foundation.layout.BoxWithConstraints
 import androidx.compose.foundation.layout.fillMaxSize
@@ -26,6 +28,7 @@ import androidx.core.view.WindowInsetsControllerCompat
 import androidx.datastore.core.DataStore
 import androidx.datastore.preferences.core.Preferences
 import androidx.datastore.preferences.preferencesDataStore
+import androidx.lifecycle.lifecycleScope
 import androidx.lifecycle.viewmodel.compose.viewModel
 import androidx.navigation.compose.NavHost
 import androidx.navigation.compose.composable
@@ -34,6 +37,7 @@ import com.anvlkv.redsiren.app.AppAbout
 import com.anvlkv.redsiren.app.AppInstrument
 import com.anvlkv.redsiren.app.AppIntro
 import com.anvlkv.redsiren.app.AppTuner
+import com.anvlkv.redsiren.app.isReducedMotionEnabled
 import com.anvlkv.redsiren.core.typegen.Event
 import com.anvlkv.redsiren.core.typegen.InstrumentEV
 import com.anvlkv.redsiren.core.typegen.IntroEV
@@ -67,22 +71,40 @@ class MainActivity : ComponentActivity() {
                 core!!.store = this.baseContext.dataStore
 
                 Surface {
-                    RedSiren(core!!)
+                    RedSiren(core!!, intent?.data?.toString())
                 }
             })
         }
     }
+
+    override fun onNewIntent(intent: Intent) {
+        super.onNewIntent(intent)
+        val link = intent.data?.toString() ?: return
+        lifecycleScope.launch {
+            core?.update(Event.DeepLink(link))
+        }
+    }
 }
 
 
 @OptIn(ExperimentalPermissionsApi::class)
 @Composable
-fun RedSiren(core: Core) {
+fun RedSiren(core: Core, link: String?) {
     val navController = rememberNavController()
     val coroutineScope = rememberCoroutineScope()
+    val reducedMotion = isReducedMotionEnabled(LocalContext.current.contentResolver)
 
     LaunchedEffect(core) {
-        core.update(Event.Start())
+        core.update(Event.Start(reducedMotion))
+        if (link != null) {
+            core.update(Event.DeepLink(link))
+        }
+    }
+
+    BackHandler(enabled = core.view.can_go_back) {
+        coroutineScope.launch {
+            core.update(Event.Back())
+        }
     }
 
     val recordAudioPermissionState = rememberPermissionState(
@@ -145,25 +167,19 @@ fun RedSiren(core: Core) {
     }
 
     fun navigateTo(act: CoreActivity) {
-        when (act) {
-            is CoreActivity.Intro -> {
-                navController.navigate("intro")
-            }
-
-            is CoreActivity.Play -> {
-                navController.navigate("play")
-            }
-
-            is CoreActivity.Tune -> {
-                navController.navigate("tune")
-            }
-
-            is CoreActivity.Listen -> {
-                navController.navigate("listen")
-            }
+        val route = when (act) {
+            is CoreActivity.Intro -> "intro"
+            is CoreActivity.Play -> "play"
+            is CoreActivity.Tune -> "tune"
+            is CoreActivity.Listen -> "listen"
+            is CoreActivity.About -> "about"
+            else -> "intro"
+        }
 
-            is CoreActivity.About -> {
-                navController.navigate("about")
+        // history is kept by the core, the system back leaves the app once it can't go back
+        navController.navigate(route) {
+            popUpTo(navController.graph.id) {
+                inclusive = true
             }
         }
     }
@@ -181,10 +197,12 @@ fun RedSiren(core: Core) {
     }
 
 
-    LaunchedEffect(core.animationSender) {
-        if (core.animationSender != null) {
+    LaunchedEffect(core.animationSenders.isEmpty()) {
+        if (core.animationSenders.isNotEmpty()) {
             val listener = fun(_: TimeAnimator, time: Long, _: Long) {
-                core.animationSender?.trySend(time)?.getOrNull()
+                for (sender in core.animationSenders.values) {
+                    sender.trySend(time).getOrNull()
+                }
             }
             animator = TimeAnimator()
             animator!!.setTimeListener(listener)
@@ -198,6 +216,15 @@ fun RedSiren(core: Core) {
         }
     }
 
+    LaunchedEffect(core.animationPaused) {
+        if (core.animationPaused) {
+            animator?.pause()
+        }
+        else {
+            animator?.resume()
+        }
+    }
+
 
 
     val context = LocalContext.current
@@ -19,7 +19,6 @@ import androidx.compose.ui.graphics.TransformOrigin
 import androidx.compose.ui.graphics.graphicsLayer
 import androidx.compose.ui.graphics.vector.ImageVector
 import androidx.compose.ui.graphics.vector.rememberVectorPainter
-import androidx.compose.ui.platform.LocalContext
 import androidx.compose.ui.res.vectorResource
 import androidx.compose.ui.unit.dp
 import com.anvlkv.redsiren.R
@@ -44,13 +43,6 @@ fun AppIntro(vm: IntroVM, ev: (ev: IntroEV) -> Unit) {
 
     val sirenPainter = rememberVectorPainter(image = sirenComp)
 
-    val reducedMotion = isReducedMotionEnabled(LocalContext.current.contentResolver)
-
-
-    LaunchedEffect(reducedMotion) {
-        ev(IntroEV.SetReducedMotion(reducedMotion))
-    }
-
     LaunchedEffect(Unit) {
         ev(IntroEV.Start())
     }
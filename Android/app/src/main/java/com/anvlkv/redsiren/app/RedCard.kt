@@ -34,6 +34,7 @@ fun RedCard(
         is MenuPosition.TopRight -> position.value
         is MenuPosition.TopLeft -> position.value
         is MenuPosition.BottomLeft -> position.value
+        is MenuPosition.BottomRight -> position.value
         is MenuPosition.Center -> position.value
         else -> throw Error("unknown position")
     }
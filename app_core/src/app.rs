@@ -2,7 +2,7 @@ use std::sync::{Arc, Mutex};
 
 pub use crux_core::App;
 use crux_core::{render::Render, Capability};
-use crux_kv::KeyValue;
+use crux_kv::{KeyValue, KeyValueOutput};
 use crux_macros::Effect;
 use hecs::World;
 use serde::{Deserialize, Serialize};
@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use crate::{animate::Animate, geometry::Rect};
 pub use instrument::Instrument;
 pub use intro::Intro;
-pub use navigate::Navigate;
+pub use navigate::{Navigate, Route};
 pub use play::Play;
 pub use tuner::Tuner;
 
@@ -18,6 +18,7 @@ use self::{
     instrument::InstrumentCapabilities, intro::IntroCapabilities, tuner::TunerCapabilities,
 };
 
+pub mod a11y;
 pub mod animate;
 pub mod instrument;
 pub mod intro;
@@ -25,6 +26,11 @@ pub mod navigate;
 pub mod play;
 pub mod tuner;
 
+const HANDEDNESS_KEY: &str = "handedness";
+const REDUCED_MOTION_KEY: &str = "reduced_motion";
+const F0_MODE_KEY: &str = "f0_mode";
+const TUNING_SYSTEM_KEY: &str = "tuning_system";
+
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub enum Activity {
     #[default]
@@ -42,7 +48,18 @@ pub struct Model {
     activity: Activity,
     _world: Arc<Mutex<World>>,
     config: Option<instrument::Config>,
+    f0_mode: instrument::F0Mode,
+    tuning_system: instrument::TuningSystem,
+    handedness: instrument::Handedness,
+    reduced_motion: bool,
+    hinge: Option<Rect>,
+    orientation: Option<instrument::Orientation>,
+    screen: Option<(f64, f64, f64, [f64; 4])>,
+    config_error: Option<String>,
     view_box: Rect,
+    a11y_focus: Option<a11y::A11yTarget>,
+    history: navigate::History,
+    link_error: Option<String>,
 }
 
 impl Default for Model {
@@ -56,6 +73,17 @@ impl Default for Model {
             activity: Default::default(),
             view_box: Default::default(),
             config: None,
+            f0_mode: Default::default(),
+            tuning_system: Default::default(),
+            handedness: Default::default(),
+            reduced_motion: false,
+            hinge: None,
+            orientation: None,
+            screen: None,
+            config_error: None,
+            a11y_focus: None,
+            history: Default::default(),
+            link_error: None,
         }
     }
 }
@@ -67,15 +95,38 @@ pub struct ViewModel {
     pub tuner: tuner::TunerVM,
     pub instrument: instrument::InstrumentVM,
     pub view_box: Rect,
+    pub config_error: Option<String>,
+    pub timeline_error: Option<String>,
+    pub a11y: a11y::A11yTree,
+    pub reduced_motion: bool,
+    pub route: Route,
+    pub can_go_back: bool,
+    pub can_go_forward: bool,
+    pub link_error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub enum Event {
-    Start,
+    /// `reduced_motion` is the system preference, a stored one overrides it.
+    Start {
+        reduced_motion: bool,
+    },
     TunerEvent(tuner::TunerEV),
     InstrumentEvent(instrument::InstrumentEV),
     IntroEvent(intro::IntroEV),
     ConfigureApp(instrument::Config),
+    SetF0(instrument::F0Mode),
+    F0ModeKV(KeyValueOutput),
+    TuningSystemKV(KeyValueOutput),
+    SetHandedness(instrument::Handedness),
+    HandednessKV(KeyValueOutput),
+    SetReducedMotion(bool),
+    ReducedMotionKV(KeyValueOutput),
+    /// Hinge or fold to keep the keyboard clear of, in screen space.
+    /// Core only for now, no shell reports folds yet.
+    SetHinge(Option<Rect>),
+    /// Rotates and mirrors the keyboard, `None` follows the screen.
+    SetOrientation(Option<instrument::Orientation>),
     CreateConfigAndConfigureApp {
         width: f64,
         height: f64,
@@ -84,7 +135,14 @@ pub enum Event {
     },
     ReflectActivity(Activity),
     Menu(Activity),
+    Back,
+    Forward,
+    /// Opens a path or URL such as `/tune?preset=street`.
+    DeepLink(String),
     Capture(play::CaptureOutput),
+    A11y(a11y::A11yEV),
+    /// Pauses animations while hidden, slows them down for debugging.
+    Clock(animate::ClockEV),
 }
 
 impl Eq for Event {}
@@ -124,6 +182,7 @@ impl From<&RedSirenCapabilities> for TunerCapabilities {
             render: incoming.render.map_event(super::Event::TunerEvent),
             play: incoming.play.map_event(super::Event::TunerEvent),
             navigate: incoming.navigate.map_event(super::Event::TunerEvent),
+            animate: incoming.animate.map_event(super::Event::TunerEvent),
         }
     }
 }
@@ -134,6 +193,7 @@ impl From<&RedSirenCapabilities> for InstrumentCapabilities {
             render: incoming.render.map_event(super::Event::InstrumentEvent),
             play: incoming.play.map_event(super::Event::InstrumentEvent),
             navigate: incoming.navigate.map_event(super::Event::InstrumentEvent),
+            animate: incoming.animate.map_event(super::Event::InstrumentEvent),
         }
     }
 }
@@ -148,15 +208,23 @@ impl App for RedSiren {
         log::trace!("app msg: {:?}", msg);
 
         match msg {
-            Event::Start => {
+            Event::Start { reduced_motion } => {
                 self.tuner.update(
                     tuner::TunerEV::CheckHasTuning,
                     &mut model.tuner,
                     &caps.into(),
                 );
+                self.apply_reduced_motion(reduced_motion, model, caps);
+                caps.key_value.read(HANDEDNESS_KEY, Event::HandednessKV);
+                caps.key_value
+                    .read(REDUCED_MOTION_KEY, Event::ReducedMotionKV);
+                caps.key_value.read(F0_MODE_KEY, Event::F0ModeKV);
+                caps.key_value
+                    .read(TUNING_SYSTEM_KEY, Event::TuningSystemKV);
                 caps.render.render();
             }
             Event::ReflectActivity(act) => {
+                model.history.arrive(act);
                 model.activity = act;
                 model.intro.current_activity = act;
                 caps.render.render();
@@ -188,6 +256,31 @@ impl App for RedSiren {
                     );
                 }
             }
+            Event::Back => {
+                if let Some(act) = model.history.go_back() {
+                    self.update(Event::Menu(act), model, caps);
+                }
+            }
+            Event::Forward => {
+                if let Some(act) = model.history.go_forward() {
+                    self.update(Event::Menu(act), model, caps);
+                }
+            }
+            Event::DeepLink(link) => {
+                match Route::parse(link.as_str()) {
+                    Ok(route) => {
+                        model.link_error = None;
+                        if let Some(act) = model.history.open(route) {
+                            self.update(Event::Menu(act), model, caps);
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("link not opened: {e}");
+                        model.link_error = Some(e.to_string());
+                    }
+                }
+                caps.render.render();
+            }
             Event::Menu(act) => {
                 log::debug!("menu {act:?}");
                 match (model.activity, act) {
@@ -215,18 +308,10 @@ impl App for RedSiren {
                         } else {
                             tuner::State::None
                         };
-                        self.intro.update(
-                            intro::IntroEV::Menu(act),
-                            &mut model.intro,
-                            &caps.into(),
-                        );
+                        self.intro_menu(act, model, caps);
                     }
-                    (Activity::Intro, Activity::About) => {
-                        self.intro.update(
-                            intro::IntroEV::Menu(act),
-                            &mut model.intro,
-                            &caps.into(),
-                        );
+                    (Activity::Intro, Activity::Intro) | (Activity::Intro, Activity::About) => {
+                        self.intro_menu(act, model, caps);
                     }
                     (Activity::About, Activity::Intro) => {
                         self.intro.update(
@@ -253,6 +338,18 @@ impl App for RedSiren {
                         );
                         self.update(Event::ReflectActivity(Activity::Intro), model, caps);
                     }
+                    (Activity::Play, Activity::Intro) => {
+                        self.instrument.update(
+                            instrument::InstrumentEV::Playback(instrument::PlaybackEV::Play(false)),
+                            &mut model.instrument,
+                            &caps.into(),
+                        );
+                        self.intro.update(
+                            intro::IntroEV::Menu(act),
+                            &mut model.intro,
+                            &caps.into(),
+                        );
+                    }
                     (Activity::Play, Activity::Play) => {
                         self.instrument.update(
                             instrument::InstrumentEV::Playback(instrument::PlaybackEV::Play(
@@ -288,7 +385,10 @@ impl App for RedSiren {
                             );
                         }
                     }
-                    _ => todo!("transition not implemented"),
+                    (from, to) => {
+                        log::warn!("no transition from {from:?} to {to:?}");
+                        caps.navigate.to(to);
+                    }
                 }
             }
             Event::CreateConfigAndConfigureApp {
@@ -297,10 +397,152 @@ impl App for RedSiren {
                 dpi,
                 safe_areas,
             } => {
-                let config = instrument::Config::new(width, height, dpi, safe_areas);
-                self.update(Event::ConfigureApp(config), model, caps);
+                model.screen = Some((width, height, dpi, safe_areas));
+                let orientation = model
+                    .orientation
+                    .unwrap_or_else(|| instrument::Orientation::for_screen(width, height));
+                match instrument::Config::try_new_tuned(
+                    width,
+                    height,
+                    dpi,
+                    safe_areas,
+                    model.hinge,
+                    orientation,
+                    model.f0_mode,
+                ) {
+                    Ok(config) => {
+                        model.config_error = None;
+                        let config = config
+                            .with_handedness(model.handedness)
+                            .with_tuning_system(model.tuning_system.clone());
+                        self.update(Event::ConfigureApp(config), model, caps);
+                    }
+                    Err(e) => {
+                        log::error!("config: {e}");
+                        model.config_error = Some(e.to_string());
+                        caps.render.render();
+                    }
+                }
+            }
+            Event::SetHinge(hinge) => {
+                model.hinge = hinge;
+                if let Some((width, height, dpi, safe_areas)) = model.screen {
+                    self.update(
+                        Event::CreateConfigAndConfigureApp {
+                            width,
+                            height,
+                            dpi,
+                            safe_areas,
+                        },
+                        model,
+                        caps,
+                    );
+                }
             }
+            Event::SetOrientation(orientation) => {
+                model.orientation = orientation;
+                if let Some((width, height, dpi, safe_areas)) = model.screen {
+                    self.update(
+                        Event::CreateConfigAndConfigureApp {
+                            width,
+                            height,
+                            dpi,
+                            safe_areas,
+                        },
+                        model,
+                        caps,
+                    );
+                }
+            }
+            Event::SetF0(f0_mode) => {
+                caps.key_value.write(
+                    F0_MODE_KEY,
+                    bincode::serialize(&f0_mode).expect("serialize f0 mode"),
+                    Event::F0ModeKV,
+                );
+                self.apply_f0(f0_mode, model, caps);
+            }
+            Event::F0ModeKV(kv) => match kv {
+                KeyValueOutput::Read(value) => {
+                    let f0_mode =
+                        value.and_then(|d| bincode::deserialize::<instrument::F0Mode>(&d).ok());
+                    if let Some(f0_mode) = f0_mode {
+                        self.apply_f0(f0_mode, model, caps);
+                    }
+                }
+                KeyValueOutput::Write(success) => {
+                    if !success {
+                        log::warn!("f0 mode not persisted");
+                    }
+                }
+            },
+            Event::TuningSystemKV(kv) => match kv {
+                KeyValueOutput::Read(value) => {
+                    let tuning_system = value
+                        .and_then(|d| bincode::deserialize::<instrument::TuningSystem>(&d).ok());
+                    if let Some(tuning_system) = tuning_system {
+                        model.tuning_system = tuning_system.clone();
+                        self.instrument.update(
+                            instrument::InstrumentEV::SetTuningSystem(tuning_system),
+                            &mut model.instrument,
+                            &caps.into(),
+                        );
+                    }
+                }
+                KeyValueOutput::Write(success) => {
+                    if !success {
+                        log::warn!("tuning system not persisted");
+                    }
+                }
+            },
+            Event::SetHandedness(handedness) => {
+                caps.key_value.write(
+                    HANDEDNESS_KEY,
+                    bincode::serialize(&handedness).expect("serialize handedness"),
+                    Event::HandednessKV,
+                );
+                self.apply_handedness(handedness, model, caps);
+            }
+            Event::HandednessKV(kv) => match kv {
+                KeyValueOutput::Read(value) => {
+                    let handedness =
+                        value.and_then(|d| bincode::deserialize::<instrument::Handedness>(&d).ok());
+                    if let Some(handedness) = handedness {
+                        self.apply_handedness(handedness, model, caps);
+                    }
+                }
+                KeyValueOutput::Write(success) => {
+                    if !success {
+                        log::warn!("handedness not persisted");
+                    }
+                }
+            },
+            Event::SetReducedMotion(reduced_motion) => {
+                caps.key_value.write(
+                    REDUCED_MOTION_KEY,
+                    bincode::serialize(&reduced_motion).expect("serialize reduced motion"),
+                    Event::ReducedMotionKV,
+                );
+                self.apply_reduced_motion(reduced_motion, model, caps);
+            }
+            Event::ReducedMotionKV(kv) => match kv {
+                KeyValueOutput::Read(value) => {
+                    if let Some(reduced_motion) =
+                        value.and_then(|d| bincode::deserialize::<bool>(&d).ok())
+                    {
+                        self.apply_reduced_motion(reduced_motion, model, caps);
+                    }
+                }
+                KeyValueOutput::Write(success) => {
+                    if !success {
+                        log::warn!("reduced motion not persisted");
+                    }
+                }
+            },
             Event::ConfigureApp(config) => {
+                model.f0_mode = config.f0_mode;
+                model.tuning_system = config.tuning_system.clone();
+                model.handedness = config.handedness;
                 self.instrument.update(
                     instrument::InstrumentEV::CreateWithConfig(config.clone()),
                     &mut model.instrument,
@@ -325,6 +567,16 @@ impl App for RedSiren {
             Event::InstrumentEvent(event) => {
                 self.instrument
                     .update(event, &mut model.instrument, &caps.into());
+
+                // tunings set or loaded on the instrument outlive its config
+                if model.instrument.config.tuning_system != model.tuning_system {
+                    model.tuning_system = model.instrument.config.tuning_system.clone();
+                    caps.key_value.write(
+                        TUNING_SYSTEM_KEY,
+                        bincode::serialize(&model.tuning_system).expect("serialize tuning system"),
+                        Event::TuningSystemKV,
+                    );
+                }
             }
             Event::TunerEvent(event) => {
                 self.tuner.update(event, &mut model.tuner, &caps.into());
@@ -341,16 +593,71 @@ impl App for RedSiren {
                         &caps.into(),
                     );
                 }
-                play::CaptureOutput::CaptureNodesData(d) => {
+                play::CaptureOutput::NodeLevels(d) => {
                     self.instrument.update(
-                        instrument::InstrumentEV::NodeSnoopData(d),
+                        instrument::InstrumentEV::NodeLevelsData(d),
+                        &mut model.instrument,
+                        &caps.into(),
+                    );
+                }
+                play::CaptureOutput::CaptureLevels(d) => {
+                    self.instrument.update(
+                        instrument::InstrumentEV::LevelsData(d),
+                        &mut model.instrument,
+                        &caps.into(),
+                    );
+                }
+                play::CaptureOutput::Spectrum(d) => {
+                    self.instrument.update(
+                        instrument::InstrumentEV::SpectrumData(d),
                         &mut model.instrument,
                         &caps.into(),
                     );
                 }
-
             },
             Event::IntroEvent(event) => self.intro.update(event, &mut model.intro, &caps.into()),
+            Event::Clock(ev) => {
+                match ev {
+                    animate::ClockEV::Pause => caps.animate.pause(),
+                    animate::ClockEV::Resume => caps.animate.resume(),
+                    animate::ClockEV::SetTimeScale(_) => {}
+                }
+                self.intro.update(
+                    intro::IntroEV::Clock(ev.clone()),
+                    &mut model.intro,
+                    &caps.into(),
+                );
+                self.tuner.update(
+                    tuner::TunerEV::Clock(ev.clone()),
+                    &mut model.tuner,
+                    &caps.into(),
+                );
+                self.instrument.update(
+                    instrument::InstrumentEV::Clock(ev),
+                    &mut model.instrument,
+                    &caps.into(),
+                );
+            }
+            Event::A11y(ev) => match ev {
+                a11y::A11yEV::Focus(target) => {
+                    model.a11y_focus = target;
+                    caps.render.render();
+                }
+                a11y::A11yEV::FocusNext => {
+                    model.a11y_focus = self.a11y_tree(model).next();
+                    caps.render.render();
+                }
+                a11y::A11yEV::FocusPrevious => {
+                    model.a11y_focus = self.a11y_tree(model).previous();
+                    caps.render.render();
+                }
+                a11y::A11yEV::Act(action) => {
+                    if let Some(target) = self.a11y_tree(model).focus {
+                        self.a11y_act(target, action, model, caps);
+                    }
+                }
+                a11y::A11yEV::ActOn(target, action) => self.a11y_act(target, action, model, caps),
+            },
         }
     }
 
@@ -361,9 +668,273 @@ impl App for RedSiren {
             intro: self.intro.view(&model.intro),
             instrument: self.instrument.view(&model.instrument),
             view_box: model.view_box,
+            config_error: model.config_error.clone(),
+            timeline_error: model.intro.timeline_error.clone(),
+            a11y: self.a11y_tree(model),
+            reduced_motion: model.reduced_motion,
+            route: model.history.current.clone(),
+            can_go_back: model.history.can_go_back(),
+            can_go_forward: model.history.can_go_forward(),
+            link_error: model.link_error.clone(),
+        }
+    }
+}
+
+impl RedSiren {
+    /// Stops playback started for a transition to play the intro doesn't
+    /// finish anymore.
+    fn intro_menu(&self, act: Activity, model: &mut Model, caps: &RedSirenCapabilities) {
+        let to_play = model.intro.transition_to == Some(Activity::Play);
+        self.intro
+            .update(intro::IntroEV::Menu(act), &mut model.intro, &caps.into());
+        if to_play && model.intro.transition_to != Some(Activity::Play) {
+            self.instrument.update(
+                instrument::InstrumentEV::Playback(instrument::PlaybackEV::Play(false)),
+                &mut model.instrument,
+                &caps.into(),
+            );
+        }
+    }
+
+    /// Lays the keyboard out again, the number of buttons depends on f0.
+    fn apply_f0(
+        &self,
+        f0_mode: instrument::F0Mode,
+        model: &mut Model,
+        caps: &RedSirenCapabilities,
+    ) {
+        model.f0_mode = f0_mode;
+        if let Some((width, height, dpi, safe_areas)) = model.screen {
+            self.update(
+                Event::CreateConfigAndConfigureApp {
+                    width,
+                    height,
+                    dpi,
+                    safe_areas,
+                },
+                model,
+                caps,
+            );
+        }
+    }
+
+    fn apply_handedness(
+        &self,
+        handedness: instrument::Handedness,
+        model: &mut Model,
+        caps: &RedSirenCapabilities,
+    ) {
+        model.handedness = handedness;
+        if let Some(config) = model.config.clone() {
+            let config = config.with_handedness(handedness);
+            self.update(Event::ConfigureApp(config), model, caps);
+        }
+    }
+
+    fn apply_reduced_motion(
+        &self,
+        reduced_motion: bool,
+        model: &mut Model,
+        caps: &RedSirenCapabilities,
+    ) {
+        model.reduced_motion = reduced_motion;
+        self.intro.update(
+            intro::IntroEV::SetReducedMotion(reduced_motion),
+            &mut model.intro,
+            &caps.into(),
+        );
+        self.tuner.update(
+            tuner::TunerEV::SetReducedMotion(reduced_motion),
+            &mut model.tuner,
+            &caps.into(),
+        );
+        self.instrument.update(
+            instrument::InstrumentEV::SetReducedMotion(reduced_motion),
+            &mut model.instrument,
+            &caps.into(),
+        );
+    }
+
+    /// Menu first, then the nodes or pairs of the current activity.
+    fn a11y_tree(&self, model: &Model) -> a11y::A11yTree {
+        let playing = model.instrument.playing;
+        let nodes = match model.activity {
+            Activity::Intro => a11y::menu(
+                &[Activity::Play, Activity::Tune, Activity::About],
+                model.intro.layout.menu_position.rect(),
+                playing,
+            ),
+            Activity::Play => match model.instrument.layout.as_ref() {
+                Some(layout) => {
+                    let mut nodes = a11y::menu(
+                        &[Activity::Play, Activity::Tune],
+                        layout.menu_position.rect(),
+                        playing,
+                    );
+                    let instrument_nodes = self.instrument.get_nodes(&model.instrument);
+                    nodes.extend(a11y::nodes(&instrument_nodes, layout));
+                    nodes
+                }
+                None => vec![],
+            },
+            Activity::Tune => {
+                let mut nodes = a11y::menu(
+                    &[Activity::Intro],
+                    model.tuner.menu_position.rect(),
+                    playing,
+                );
+                nodes.extend(a11y::pairs(&self.tuner.get_pairs(&model.tuner)));
+                nodes
+            }
+            Activity::About => a11y::menu(&[Activity::Intro], &model.view_box, playing),
+            Activity::Listen => vec![],
+        };
+
+        a11y::A11yTree { nodes, focus: None }.with_focus(model.a11y_focus)
+    }
+
+    fn a11y_act(
+        &self,
+        target: a11y::A11yTarget,
+        action: a11y::A11yAction,
+        model: &mut Model,
+        caps: &RedSirenCapabilities,
+    ) {
+        use a11y::{A11yAction, A11yTarget};
+        use instrument::node::{MAX_GAIN_DB, MIN_GAIN_DB};
+
+        let tree = self.a11y_tree(model);
+        let supported = tree
+            .get(&target)
+            .map_or(false, |node| node.supports(action));
+        if !supported {
+            log::warn!("a11y: no {action:?} on {target:?}");
+            return;
+        }
+
+        match target {
+            A11yTarget::Menu(act) => self.update(Event::Menu(act), model, caps),
+            A11yTarget::Node(f_n) => {
+                let Some(node) = self
+                    .instrument
+                    .get_nodes(&model.instrument)
+                    .into_iter()
+                    .find(|node| node.f_n == f_n)
+                else {
+                    return;
+                };
+                let event = match action {
+                    A11yAction::Activate => instrument::InstrumentEV::SetNodeMute(f_n, !node.muted),
+                    A11yAction::Solo => instrument::InstrumentEV::SetNodeSolo(f_n, !node.solo),
+                    A11yAction::Increase => instrument::InstrumentEV::SetNodeGain(
+                        f_n,
+                        (node.gain_db + a11y::GAIN_STEP_DB).min(MAX_GAIN_DB),
+                    ),
+                    A11yAction::Decrease => instrument::InstrumentEV::SetNodeGain(
+                        f_n,
+                        (node.gain_db - a11y::GAIN_STEP_DB).max(MIN_GAIN_DB),
+                    ),
+                    A11yAction::Raise | A11yAction::Lower => return,
+                };
+                self.update(Event::InstrumentEvent(event), model, caps);
+            }
+            A11yTarget::Pair(f_n) => {
+                // higher frequencies are to the left, louder ones further up
+                let step = model.tuner.config.button_size / 2.0;
+                let by = match action {
+                    A11yAction::Raise => (-step, 0.0),
+                    A11yAction::Lower => (step, 0.0),
+                    A11yAction::Increase => (0.0, -step),
+                    A11yAction::Decrease => (0.0, step),
+                    A11yAction::Activate | A11yAction::Solo => return,
+                };
+                self.update(
+                    Event::TunerEvent(tuner::TunerEV::NudgePair(f_n, by)),
+                    model,
+                    caps,
+                );
+            }
         }
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crux_core::testing::AppTester;
+
+    use super::*;
+
+    fn resize(app: &AppTester<RedSiren, Effect>, model: &mut Model, width: f64, height: f64) {
+        _ = app.update(
+            Event::CreateConfigAndConfigureApp {
+                width,
+                height,
+                dpi: 96.0,
+                safe_areas: [0.0; 4],
+            },
+            model,
+        );
+    }
+
+    fn button_freqs(model: &Model) -> BTreeMap<usize, f32> {
+        let world = model.instrument.world.lock().expect("world lock");
+        let freqs = world
+            .query::<&instrument::keyboard::Button>()
+            .iter()
+            .map(|(_, button)| (button.f_n, button.freq))
+            .collect();
+        freqs
+    }
+
+    #[test]
+    fn keeps_the_tuning_system_across_rotations() {
+        let app = AppTester::<RedSiren, Effect>::default();
+        let mut model = Model::default();
+        let scl = "! pentatonic.scl\nPentatonic\n5\n!\n9/8\n5/4\n3/2\n5/3\n2/1\n";
+
+        resize(&app, &mut model, 1920.0, 1080.0);
+        _ = app.update(
+            Event::InstrumentEvent(instrument::InstrumentEV::LoadScala(scl.to_string(), None)),
+            &mut model,
+        );
+        assert!(matches!(
+            model.tuning_system,
+            instrument::TuningSystem::Scala { .. }
+        ));
+        let tuned = button_freqs(&model);
+
+        resize(&app, &mut model, 1080.0, 1920.0);
+        let rotated = button_freqs(&model);
+
+        assert!(!rotated.is_empty());
+        for (f_n, freq) in rotated {
+            if let Some(tuned) = tuned.get(&f_n) {
+                assert_eq!(freq, *tuned, "f_n {f_n}");
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_the_voice_across_resizes() {
+        let app = AppTester::<RedSiren, Effect>::default();
+        let mut model = Model::default();
+
+        resize(&app, &mut model, 1920.0, 1080.0);
+        _ = app.update(
+            Event::InstrumentEvent(instrument::InstrumentEV::SetVoice(
+                None,
+                instrument::Voice::PluckedString,
+            )),
+            &mut model,
+        );
+        resize(&app, &mut model, 1080.0, 1920.0);
+
+        let nodes = app.view(&model).instrument.nodes;
+        assert!(!nodes.is_empty());
+        assert!(nodes
+            .iter()
+            .all(|node| node.voice == instrument::Voice::PluckedString));
+    }
+}
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::Rect,
+    instrument::{Layout, Node},
+    tuner::Pair,
+    Activity,
+};
+
+/// Gain change of a node per step, in dB.
+pub const GAIN_STEP_DB: f32 = 3.0;
+
+/// Something on screen a screen reader can describe and focus.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum A11yTarget {
+    /// Menu entry leading to an activity.
+    Menu(Activity),
+    /// Instrument node by `f_n`.
+    Node(usize),
+    /// Tuner pair by `f_n`.
+    Pair(usize),
+}
+
+/// Maps onto the ARIA role in the shells.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum A11yRole {
+    Button,
+    Slider,
+}
+
+/// What can be done with a focused element without touching it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum A11yAction {
+    /// Presses a menu entry, toggles mute on a node.
+    Activate,
+    /// Toggles solo on a node.
+    Solo,
+    /// Louder node or pair.
+    Increase,
+    /// Quieter node or pair.
+    Decrease,
+    /// Higher pair frequency.
+    Raise,
+    /// Lower pair frequency.
+    Lower,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct A11yNode {
+    pub target: A11yTarget,
+    pub role: A11yRole,
+    pub label: String,
+    pub rect: Rect,
+    pub actions: Vec<A11yAction>,
+    /// Toggle state, `None` when it isn't one.
+    pub pressed: Option<bool>,
+}
+
+impl Eq for A11yNode {}
+
+impl A11yNode {
+    pub fn supports(&self, action: A11yAction) -> bool {
+        self.actions.contains(&action)
+    }
+}
+
+/// Elements in focus order with the focused one.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct A11yTree {
+    pub nodes: Vec<A11yNode>,
+    pub focus: Option<A11yTarget>,
+}
+
+impl A11yTree {
+    /// Drops the focus when its target is no longer on screen.
+    pub fn with_focus(mut self, focus: Option<A11yTarget>) -> Self {
+        self.focus = focus.filter(|target| self.get(target).is_some());
+        self
+    }
+
+    pub fn get(&self, target: &A11yTarget) -> Option<&A11yNode> {
+        self.nodes.iter().find(|node| node.target == *target)
+    }
+
+    pub fn focused(&self) -> Option<&A11yNode> {
+        self.focus.as_ref().and_then(|target| self.get(target))
+    }
+
+    /// Target after the focused one, wrapping around.
+    pub fn next(&self) -> Option<A11yTarget> {
+        self.step(true)
+    }
+
+    /// Target before the focused one, wrapping around.
+    pub fn previous(&self) -> Option<A11yTarget> {
+        self.step(false)
+    }
+
+    fn step(&self, forward: bool) -> Option<A11yTarget> {
+        let len = self.nodes.len();
+        let at = self
+            .focus
+            .and_then(|f| self.nodes.iter().position(|n| n.target == f));
+        let at = match (at, forward) {
+            (Some(at), true) => (at + 1) % len,
+            (Some(at), false) => (at + len - 1) % len,
+            (None, true) => 0,
+            (None, false) => len.checked_sub(1)?,
+        };
+        self.nodes.get(at).map(|node| node.target)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub enum A11yEV {
+    Focus(Option<A11yTarget>),
+    FocusNext,
+    FocusPrevious,
+    /// Runs the action on the focused element.
+    Act(A11yAction),
+    /// Runs the action on the target, as a screen reader does.
+    ActOn(A11yTarget, A11yAction),
+}
+
+pub fn node_label(node: &Node) -> String {
+    let mut label = format!("node {}, {:.0} Hz", node.f_n, node.freq.0);
+    if node.muted {
+        label.push_str(", muted");
+    }
+    if node.solo {
+        label.push_str(", solo");
+    }
+    if node.gain_db != 0.0 {
+        label.push_str(&format!(", {:+.0} dB", node.gain_db));
+    }
+    label
+}
+
+pub fn pair_label(pair: &Pair) -> String {
+    match pair.value {
+        Some((freq, _)) => format!("node {}, {freq:.0} Hz, tuned", pair.f_n),
+        None => format!("node {}, not tuned", pair.f_n),
+    }
+}
+
+pub fn menu_label(act: Activity, playing: bool) -> &'static str {
+    match act {
+        Activity::Intro => "Back",
+        Activity::Tune => "Tune",
+        Activity::Play if playing => "Pause",
+        Activity::Play => "Play",
+        Activity::Listen => "Listen",
+        Activity::About => "About",
+    }
+}
+
+pub fn menu(acts: &[Activity], rect: &Rect, playing: bool) -> Vec<A11yNode> {
+    acts.iter()
+        .map(|act| A11yNode {
+            target: A11yTarget::Menu(*act),
+            role: A11yRole::Button,
+            label: menu_label(*act, playing).to_string(),
+            rect: *rect,
+            actions: vec![A11yAction::Activate],
+            pressed: (*act == Activity::Play).then_some(playing),
+        })
+        .collect()
+}
+
+/// Instrument nodes in reading order of their buttons.
+pub fn nodes(nodes: &[Node], layout: &Layout) -> Vec<A11yNode> {
+    let mut nodes = nodes
+        .iter()
+        .filter_map(|node| {
+            let at = layout.buttons.len().checked_sub(node.f_n)?;
+            let rect = *layout.buttons.get(at)?;
+            Some(A11yNode {
+                target: A11yTarget::Node(node.f_n),
+                role: A11yRole::Button,
+                label: node_label(node),
+                rect,
+                actions: vec![
+                    A11yAction::Activate,
+                    A11yAction::Solo,
+                    A11yAction::Increase,
+                    A11yAction::Decrease,
+                ],
+                pressed: Some(!node.muted),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    nodes.sort_by(|a, b| {
+        let (a, b) = (a.rect.center(), b.rect.center());
+        a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+    });
+
+    nodes
+}
+
+/// Tuner pairs, lowest node first.
+pub fn pairs(pairs: &[Pair]) -> Vec<A11yNode> {
+    let mut pairs = pairs
+        .iter()
+        .map(|pair| A11yNode {
+            target: A11yTarget::Pair(pair.f_n),
+            role: A11yRole::Slider,
+            label: pair_label(pair),
+            rect: pair.rect,
+            actions: vec![
+                A11yAction::Raise,
+                A11yAction::Lower,
+                A11yAction::Increase,
+                A11yAction::Decrease,
+            ],
+            pressed: None,
+        })
+        .collect::<Vec<_>>();
+
+    pairs.sort_by_key(|node| match node.target {
+        A11yTarget::Pair(f_n) => f_n,
+        _ => 0,
+    });
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{instrument::Voice, tuner::TriggerState};
+
+    fn node(f_n: usize) -> Node {
+        Node {
+            freq: (110.0 * f_n as f32, 110.0 * (f_n + 1) as f32),
+            f_n,
+            pan: 0,
+            triggered: 0.0,
+            voice: Voice::default(),
+            muted: false,
+            solo: false,
+            gain_db: 0.0,
+        }
+    }
+
+    fn pair(f_n: usize, value: Option<(f32, f32)>) -> Pair {
+        Pair {
+            value,
+            f_n,
+            rect: Rect::size(10.0, 10.0),
+            finger: None,
+            triggered: TriggerState::None,
+        }
+    }
+
+    #[test]
+    fn describes_nodes_and_pairs() {
+        let mut n = node(7);
+        assert_eq!(node_label(&n), "node 7, 770 Hz");
+        n.muted = true;
+        n.gain_db = 3.0;
+        assert_eq!(node_label(&n), "node 7, 770 Hz, muted, +3 dB");
+
+        assert_eq!(
+            pair_label(&pair(3, Some((770.4, 0.5)))),
+            "node 3, 770 Hz, tuned"
+        );
+        assert_eq!(pair_label(&pair(3, None)), "node 3, not tuned");
+    }
+
+    #[test]
+    fn orders_nodes_by_their_buttons() {
+        let layout = Layout {
+            buttons: vec![
+                Rect::new(0.0, 10.0, 20.0, 30.0),
+                Rect::new(20.0, 30.0, 0.0, 10.0),
+                Rect::new(0.0, 10.0, 0.0, 10.0),
+            ],
+            ..Default::default()
+        };
+        let tree = nodes(&[node(3), node(2), node(1)], &layout);
+        let order = tree.iter().map(|n| n.target).collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                A11yTarget::Node(1),
+                A11yTarget::Node(2),
+                A11yTarget::Node(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn moves_focus_around() {
+        let tree = A11yTree {
+            nodes: pairs(&[pair(2, None), pair(1, None), pair(3, None)]),
+            focus: None,
+        };
+        assert_eq!(tree.next(), Some(A11yTarget::Pair(1)));
+        assert_eq!(tree.previous(), Some(A11yTarget::Pair(3)));
+
+        let tree = tree.with_focus(Some(A11yTarget::Pair(3)));
+        assert_eq!(tree.next(), Some(A11yTarget::Pair(1)));
+        assert_eq!(tree.previous(), Some(A11yTarget::Pair(2)));
+
+        let two = A11yTree {
+            nodes: pairs(&[pair(1, None), pair(2, None)]),
+            focus: None,
+        };
+        assert_eq!(two.previous(), Some(A11yTarget::Pair(2)));
+
+        let tree = tree.with_focus(Some(A11yTarget::Node(3)));
+        assert_eq!(tree.focus, None);
+        assert_eq!(A11yTree::default().next(), None);
+    }
+}
@@ -1,12 +1,27 @@
 use crux_core::capability::{CapabilityContext, Operation};
 use crux_macros::Capability;
 use futures::StreamExt;
+use keyframe::CanTween;
 use serde::{Deserialize, Serialize};
 
+/// Handles of animations, one per module so they run side by side.
+pub const INTRO_ANIMATION: usize = 0;
+pub const TUNER_ANIMATION: usize = 1;
+pub const INSTRUMENT_ANIMATION: usize = 2;
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum AnimateOperation {
-    Start,
-    Stop,
+    /// Streams frames to the animation `id`, ending one already running with it.
+    Start {
+        id: usize,
+    },
+    Stop {
+        id: usize,
+    },
+    /// Holds frames of all animations, the started streams stay open.
+    Pause,
+    /// Sends frames again after a pause.
+    Resume,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
@@ -21,6 +36,95 @@ impl Operation for AnimateOperation {
     type Output = AnimateOperationOutput;
 }
 
+/// Longest step between two frames, in ms.
+pub const MAX_DELTA: f64 = 100.0;
+
+/// Time for a followed value to cover half the way to its target, in ms.
+pub const HALF_LIFE: f64 = 50.0;
+
+/// Distance in px under which a followed point is at its target.
+pub const SETTLED: f64 = 0.5;
+
+/// Moves `from` towards `to` for `dt` ms, the same way at any frame rate.
+pub fn follow<T: CanTween>(from: T, to: T, dt: f64) -> T {
+    T::ease(from, to, 1.0 - 0.5_f64.powf(dt / HALF_LIFE))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub enum ClockEV {
+    Pause,
+    Resume,
+    /// Speed of animations, below 1 for slow motion.
+    SetTimeScale(f64),
+}
+
+impl Eq for ClockEV {}
+
+/// Animation time made of shell timestamps.
+///
+/// A frame never advances it by more than `max_delta`, so a stalled or
+/// backgrounded shell doesn't skip an animation, and nothing passes while paused.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clock {
+    pub time: f64,
+    pub scale: f64,
+    pub max_delta: f64,
+    pub paused: bool,
+    last: Option<f64>,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            scale: 1.0,
+            max_delta: MAX_DELTA,
+            paused: false,
+            last: None,
+        }
+    }
+}
+
+impl Clock {
+    /// Animation time at the shell timestamp.
+    pub fn tick(&mut self, ts: f64) -> f64 {
+        if let Some(last) = self.last.filter(|_| !self.paused) {
+            self.time += (ts - last).clamp(0.0, self.max_delta) * self.scale;
+        }
+        self.last = Some(ts);
+        self.time
+    }
+
+    /// Animation time passed since the last frame.
+    pub fn step(&mut self, ts: f64) -> f64 {
+        let before = self.time;
+        self.tick(ts) - before
+    }
+
+    /// Starts the next frame from the current time, for a new stream.
+    pub fn restart(&mut self) {
+        self.last = None;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// The time spent paused doesn't count.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.last = None;
+    }
+
+    pub fn update(&mut self, event: ClockEV) {
+        match event {
+            ClockEV::Pause => self.pause(),
+            ClockEV::Resume => self.resume(),
+            ClockEV::SetTimeScale(scale) => self.scale = scale.max(0.0),
+        }
+    }
+}
+
 #[derive(Capability)]
 pub struct Animate<Ev> {
     context: CapabilityContext<AnimateOperation, Ev>,
@@ -34,17 +138,17 @@ where
         Self { context }
     }
 
-    pub fn start<F>(&self, notify: F, label: String)
+    pub fn start<F>(&self, id: usize, notify: F, label: String)
     where
         F: Fn(f64) -> Ev + Send + 'static,
     {
-        log::debug!("starting animation");
+        log::debug!("starting animation {id}");
 
         let context = self.context.clone();
 
         self.context.spawn({
             async move {
-                let mut stream = context.stream_from_shell(AnimateOperation::Start);
+                let mut stream = context.stream_from_shell(AnimateOperation::Start { id });
 
                 while let Some(response) = stream.next().await {
                     if let AnimateOperationOutput::Timestamp(ts) = response {
@@ -59,15 +163,69 @@ where
         });
     }
 
-    pub fn stop(&self) {
-        log::debug!("stopping animation");
+    pub fn stop(&self, id: usize) {
+        log::debug!("stopping animation {id}");
+
+        self.notify(AnimateOperation::Stop { id });
+    }
+
+    pub fn pause(&self) {
+        log::debug!("pausing animation");
 
+        self.notify(AnimateOperation::Pause);
+    }
+
+    pub fn resume(&self) {
+        log::debug!("resuming animation");
+
+        self.notify(AnimateOperation::Resume);
+    }
+
+    fn notify(&self, operation: AnimateOperation) {
         let context = self.context.clone();
-        
+
         self.context.spawn({
             async move {
-                _ = context.notify_shell(AnimateOperation::Stop).await;
+                _ = context.notify_shell(operation).await;
             }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_and_scales_frames() {
+        let mut clock = Clock::default();
+        assert_eq!(clock.tick(1000.0), 0.0);
+        assert_eq!(clock.tick(1016.0), 16.0);
+        assert_eq!(clock.tick(5000.0), 16.0 + MAX_DELTA);
+
+        clock.update(ClockEV::SetTimeScale(0.5));
+        assert_eq!(clock.tick(5016.0), 124.0);
+    }
+
+    #[test]
+    fn stands_still_while_paused() {
+        let mut clock = Clock::default();
+        clock.tick(0.0);
+        clock.tick(50.0);
+
+        clock.update(ClockEV::Pause);
+        assert_eq!(clock.tick(60.0), 50.0);
+
+        clock.update(ClockEV::Resume);
+        assert_eq!(clock.tick(9000.0), 50.0);
+        assert_eq!(clock.tick(9010.0), 60.0);
+    }
+
+    #[test]
+    fn follows_the_same_at_any_frame_rate() {
+        let at_60 = (0..6).fold(0.0, |x, _| follow(x, 100.0, 100.0 / 6.0));
+        let at_120 = (0..12).fold(0.0, |x, _| follow(x, 100.0, 100.0 / 12.0));
+        assert!((at_60 - 75.0_f64).abs() < 1e-9);
+        assert!((at_120 - 75.0_f64).abs() < 1e-9);
+    }
+}
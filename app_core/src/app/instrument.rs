@@ -1,7 +1,5 @@
-use std::{
-    cmp::Ordering,
-    sync::{Arc, Mutex},
-};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 
 use crux_core::render::Render;
 use crux_core::App;
@@ -10,12 +8,20 @@ use hecs::{Entity, World};
 use mint::Point2;
 use serde::{Deserialize, Serialize};
 
-pub use config::Config;
-pub use layout::{Layout, LayoutRoot};
-use node::spawn_all_nodes;
+pub use config::{Config, Decimation, F0Mode, Handedness};
+pub use layout::{Layout, LayoutRoot, LayoutTarget};
+use node::{node_gains, spawn_all_nodes, trigger_level, NodeMix};
 pub use node::Node;
-
-use crate::{play::Play, tuner::TuningValue, Navigate};
+pub use orientation::{Orientation, Rotation};
+pub use tuning_system::TuningSystem;
+pub use voice::Voice;
+
+use crate::{
+    animate::{Animate, Clock, ClockEV, INSTRUMENT_ANIMATION},
+    play::{Effects, Level, MasterBus, NodeLevel, NodeMetering, Play, Stream, Subscriptions},
+    tuner::TuningValue,
+    Navigate,
+};
 
 use self::string::OutboundString;
 
@@ -23,7 +29,11 @@ pub mod config;
 pub mod keyboard;
 pub mod layout;
 pub mod node;
+pub mod orientation;
+pub mod scala;
 pub mod string;
+pub mod tuning_system;
+pub mod voice;
 
 #[derive(Default)]
 pub struct Instrument;
@@ -37,12 +47,30 @@ pub struct Model {
     pub keyboard: Option<Entity>,
     pub root: Option<Entity>,
     pub nodes: Vec<Entity>,
+    /// Voice of the nodes without one of their own.
+    pub voice: Voice,
+    /// Voices set for single nodes by `f_n`, over `voice`.
+    pub voices: BTreeMap<usize, Voice>,
+    /// Mute, solo and gain set on nodes by `f_n`.
+    pub mixes: BTreeMap<usize, NodeMix>,
     pub playing: bool,
     pub layout: Option<Layout>,
     pub setup_complete: bool,
     pub configured: bool,
     pub tuning: Vec<TuningValue>,
     pub snooped: Vec<f32>,
+    pub master_bus: MasterBus,
+    pub effects: Effects,
+    pub node_metering: NodeMetering,
+    pub levels: Vec<Level>,
+    pub spectrum: Vec<(f32, f32)>,
+    pub subscriptions: Subscriptions,
+    pub tuning_error: Option<String>,
+    pub reduced_motion: bool,
+    pub clock: Clock,
+    /// The outbound string animation runs, while the string streams or eases
+    /// to the last snooped data.
+    pub animating: bool,
 }
 
 impl Model {
@@ -62,6 +90,11 @@ pub struct InstrumentVM {
     pub playing: bool,
     pub layout: Layout,
     pub data_out: Vec<Point2<f64>>,
+    pub master_bus: MasterBus,
+    pub effects: Effects,
+    pub levels: Vec<Level>,
+    pub spectrum: Vec<(f32, f32)>,
+    pub tuning_error: Option<String>,
 }
 
 impl Eq for InstrumentVM {}
@@ -85,8 +118,32 @@ pub enum InstrumentEV {
     PlayOpPlay(bool),
     PlayOpPause(bool),
     SnoopData(Vec<f32>),
-    NodeSnoopData(Vec<(usize, Vec<f32>)>),
-    RequestSnoops,
+    NodeLevelsData(Vec<NodeLevel>),
+    LevelsData(Vec<Level>),
+    SpectrumData(Vec<(f32, f32)>),
+    Subscribe(Stream, f32),
+    Unsubscribe(Stream),
+    PlayOpSubscribe(bool),
+    SetNodeMute(usize, bool),
+    SetNodeSolo(usize, bool),
+    SetNodeGain(usize, f32),
+    PlayOpNodeGains(bool),
+    SetMasterBus(MasterBus),
+    SetVoice(Option<usize>, Voice),
+    SetTuningSystem(TuningSystem),
+    LoadScala(String, Option<String>),
+    SetEffects(Effects),
+    SetNodeMetering(NodeMetering),
+    PlayOpNodeMetering(bool),
+    PlayOpEffects(bool),
+    LoadSample(usize, Vec<u8>),
+    LoadSamplePath(usize, String),
+    PlayOpLoadSample(bool),
+    PlayOpMasterBus(bool),
+    /// Keeps the outbound string still instead of following the output.
+    SetReducedMotion(bool),
+    TsNext(f64),
+    Clock(ClockEV),
 }
 
 impl Eq for InstrumentEV {}
@@ -98,6 +155,7 @@ pub struct InstrumentCapabilities {
     pub render: Render<InstrumentEV>,
     pub play: Play<InstrumentEV>,
     pub navigate: Navigate<InstrumentEV>,
+    pub animate: Animate<InstrumentEV>,
 }
 
 impl App for Instrument {
@@ -116,6 +174,20 @@ impl App for Instrument {
                 {
                     let mut world = model.world.lock().expect("world lock");
 
+                    // queries over the world find only the current layout
+                    if let Some(keyboard) = model.keyboard.take() {
+                        keyboard::Keyboard::despawn(&mut world, keyboard);
+                    }
+                    for e in model
+                        .nodes
+                        .drain(..)
+                        .chain(model.root.take())
+                        .chain(model.inbound.take())
+                        .chain(model.outbound.take())
+                    {
+                        _ = world.despawn(e);
+                    }
+
                     let inbound = string::InboundString::spawn(&mut world, &config);
                     let outbound = string::OutboundString::spawn(&mut world, &config);
                     let keyboard = keyboard::Keyboard::spawn(&mut world, &config);
@@ -130,7 +202,16 @@ impl App for Instrument {
                     _ = model.outbound.insert(outbound);
                     _ = model.keyboard.insert(keyboard);
 
-                    model.nodes = spawn_all_nodes(&mut world);
+                    model.nodes = spawn_all_nodes(&mut world, model.voice);
+                    for e in model.nodes.iter() {
+                        let mut node = world.get::<&mut Node>(*e).expect("node for entity");
+                        if let Some(voice) = model.voices.get(&node.f_n) {
+                            node.voice = *voice;
+                        }
+                        if let Some(mix) = model.mixes.get(&node.f_n) {
+                            node.set_mix(*mix);
+                        }
+                    }
                 }
 
                 if model.playing {
@@ -156,7 +237,6 @@ impl App for Instrument {
                     caps.navigate.to(crate::Activity::Intro)
                 }
             }
-            InstrumentEV::RequestSnoops => caps.play.query_snoops(),
             InstrumentEV::PlayOpInstall(success) => {
                 if !success {
                     self.update(InstrumentEV::Playback(PlaybackEV::Error), model, caps)
@@ -176,6 +256,13 @@ impl App for Instrument {
                 if !success {
                     self.update(InstrumentEV::Playback(PlaybackEV::Error), model, caps)
                 } else {
+                    caps.play.master_bus(model.master_bus, InstrumentEV::PlayOpMasterBus);
+                    caps.play.effects(model.effects, InstrumentEV::PlayOpEffects);
+                    caps.play
+                        .node_metering(model.node_metering, InstrumentEV::PlayOpNodeMetering);
+                    for (stream, rate_hz) in model.subscriptions.0.iter() {
+                        caps.play.subscribe(*stream, *rate_hz, InstrumentEV::PlayOpSubscribe);
+                    }
                     self.update(
                         InstrumentEV::Playback(PlaybackEV::Play(model.playing)),
                         model,
@@ -183,6 +270,192 @@ impl App for Instrument {
                     )
                 }
             }
+            InstrumentEV::SetMasterBus(settings) => {
+                model.master_bus = settings;
+                if model.configured {
+                    caps.play.master_bus(model.master_bus, InstrumentEV::PlayOpMasterBus);
+                }
+                caps.render.render();
+            }
+            InstrumentEV::SetEffects(settings) => {
+                model.effects = settings;
+                if model.configured {
+                    caps.play.effects(model.effects, InstrumentEV::PlayOpEffects);
+                }
+                caps.render.render();
+            }
+            InstrumentEV::PlayOpEffects(success) => {
+                if !success {
+                    log::warn!("effects settings not applied");
+                }
+            }
+            InstrumentEV::SetNodeMetering(settings) => {
+                model.node_metering = settings;
+                if model.configured {
+                    caps.play
+                        .node_metering(model.node_metering, InstrumentEV::PlayOpNodeMetering);
+                }
+            }
+            InstrumentEV::SetReducedMotion(reduced_motion) => {
+                model.reduced_motion = reduced_motion;
+                if reduced_motion {
+                    let world = model.world.lock().expect("lock world");
+                    if let Some(mut outbound) = model
+                        .outbound
+                        .and_then(|e| world.get::<&mut OutboundString>(e).ok())
+                    {
+                        outbound.data.clear();
+                        outbound.target.clear();
+                    }
+                    if model.animating {
+                        model.animating = false;
+                        caps.animate.stop(INSTRUMENT_ANIMATION);
+                    }
+                }
+                caps.render.render();
+            }
+            InstrumentEV::PlayOpNodeMetering(success) => {
+                if !success {
+                    log::warn!("node metering settings not applied");
+                }
+            }
+            InstrumentEV::Subscribe(stream, rate_hz) => {
+                model.subscriptions.0.insert(stream, rate_hz);
+                if model.configured {
+                    caps.play.subscribe(stream, rate_hz, InstrumentEV::PlayOpSubscribe);
+                }
+            }
+            InstrumentEV::Unsubscribe(stream) => {
+                model.subscriptions.0.remove(&stream);
+                if stream == Stream::Spectrum {
+                    model.spectrum = vec![];
+                }
+                if model.configured {
+                    caps.play.unsubscribe(stream, InstrumentEV::PlayOpSubscribe);
+                }
+                caps.render.render();
+            }
+            InstrumentEV::PlayOpSubscribe(success) => {
+                if !success {
+                    log::warn!("stream subscription not changed");
+                }
+            }
+            InstrumentEV::SetNodeMute(f_n, muted) => {
+                self.update_node(model, caps, f_n, |node| node.muted = muted);
+            }
+            InstrumentEV::SetNodeSolo(f_n, solo) => {
+                self.update_node(model, caps, f_n, |node| node.solo = solo);
+            }
+            InstrumentEV::SetNodeGain(f_n, gain_db) => {
+                self.update_node(model, caps, f_n, |node| node.gain_db = gain_db);
+            }
+            InstrumentEV::PlayOpNodeGains(success) => {
+                if !success {
+                    log::warn!("node gains not applied");
+                }
+            }
+            InstrumentEV::SetVoice(f_n, voice) => {
+                {
+                    let mut world = model.world.lock().expect("world lock");
+                    match f_n {
+                        Some(f_n) => _ = model.voices.insert(f_n, voice),
+                        None => {
+                            model.voice = voice;
+                            model.voices.clear();
+                        }
+                    }
+                    for (_, node) in world
+                        .query_mut::<&mut Node>()
+                        .into_iter()
+                        .filter(|(_, node)| f_n.map_or(true, |f_n| node.f_n == f_n))
+                    {
+                        node.voice = voice;
+                    }
+                }
+
+                if model.configured {
+                    let nodes = self.get_nodes(model);
+                    caps.play.configure(
+                        &model.config,
+                        nodes.as_slice(),
+                        &model.tuning.as_slice(),
+                        InstrumentEV::PlayOpConfigure,
+                    );
+                }
+                caps.render.render();
+            }
+            InstrumentEV::LoadScala(scl, kbm) => {
+                let tuning_system = scala::Scale::parse(scl.as_str()).and_then(|scale| {
+                    let mapping = kbm
+                        .as_deref()
+                        .map(scala::KeyboardMapping::parse)
+                        .transpose()?;
+                    scale.tuning_system(mapping.as_ref())
+                });
+
+                match tuning_system {
+                    Ok(tuning_system) => {
+                        self.update(InstrumentEV::SetTuningSystem(tuning_system), model, caps)
+                    }
+                    Err(e) => {
+                        log::error!("scala file not loaded: {e}");
+                        model.tuning_error = Some(e.to_string());
+                        caps.render.render();
+                    }
+                }
+            }
+            InstrumentEV::SetTuningSystem(tuning_system) => {
+                model.tuning_error = None;
+                {
+                    let mut world = model.world.lock().expect("world lock");
+                    model.config.tuning_system = tuning_system;
+                    let f0 = model.config.f0;
+
+                    for (_, button) in world.query_mut::<&mut keyboard::Button>() {
+                        button.freq = model.config.tuning_system.band(f0, button.f_n).0;
+                    }
+                    let tracks = world
+                        .query::<&keyboard::Button>()
+                        .iter()
+                        .map(|(_, button)| (button.track, button.f_n))
+                        .collect::<Vec<_>>();
+                    for (track, f_n) in tracks {
+                        if let Ok(mut track) = world.get::<&mut keyboard::Track>(track) {
+                            track.freq = model.config.tuning_system.band(f0, f_n);
+                        }
+                    }
+                    for (_, node) in world.query_mut::<&mut Node>() {
+                        node.freq = model.config.tuning_system.band(f0, node.f_n);
+                    }
+                }
+
+                if model.configured {
+                    let nodes = self.get_nodes(model);
+                    caps.play.configure(
+                        &model.config,
+                        nodes.as_slice(),
+                        &model.tuning.as_slice(),
+                        InstrumentEV::PlayOpConfigure,
+                    );
+                }
+                caps.render.render();
+            }
+            InstrumentEV::LoadSample(slot, bytes) => {
+                caps.play.load_sample(slot, bytes, InstrumentEV::PlayOpLoadSample);
+            }
+            InstrumentEV::LoadSamplePath(slot, path) => {
+                caps.play.load_sample_path(slot, path, InstrumentEV::PlayOpLoadSample);
+            }
+            InstrumentEV::PlayOpLoadSample(success) => {
+                if !success {
+                    log::error!("sample not loaded");
+                }
+            }
+            InstrumentEV::PlayOpMasterBus(success) => {
+                if !success {
+                    log::warn!("master bus settings not applied");
+                }
+            }
             InstrumentEV::PlayOpPause(success) => {
                 if !success {
                     self.update(InstrumentEV::Playback(PlaybackEV::Error), model, caps)
@@ -223,41 +496,71 @@ impl App for Instrument {
             InstrumentEV::SnoopData(d) => {
                 model.snooped = d;
 
-                let world = model.world.lock().expect("lock world");
-                let mut outbound = model
-                    .outbound
-                    .as_ref()
-                    .map(|e| world.get::<&mut OutboundString>(*e).ok())
-                    .flatten()
-                    .expect("get string");
-
-                outbound.update_data(model.snooped.clone(), &model.config);
+                if !model.reduced_motion {
+                    let world = model.world.lock().expect("lock world");
+                    let mut outbound = model
+                        .outbound
+                        .as_ref()
+                        .map(|e| world.get::<&mut OutboundString>(*e).ok())
+                        .flatten()
+                        .expect("get string");
+
+                    outbound.update_data(model.snooped.clone(), &model.config);
+                    if !model.animating {
+                        model.animating = true;
+                        model.clock.restart();
+                        caps.animate.start(
+                            INSTRUMENT_ANIMATION,
+                            InstrumentEV::TsNext,
+                            "outbound string".to_string(),
+                        );
+                    }
+                }
+            }
+            InstrumentEV::TsNext(ts) => {
+                let dt = model.clock.step(ts);
+                let moving = {
+                    let world = model.world.lock().expect("lock world");
+                    model
+                        .outbound
+                        .and_then(|e| world.get::<&mut OutboundString>(e).ok())
+                        .map_or(false, |mut outbound| outbound.follow(dt))
+                };
+
+                // the string keeps easing between snoops while it streams
+                let streaming =
+                    model.playing && model.subscriptions.0.contains_key(&Stream::String);
+                if !moving && !streaming && model.animating {
+                    model.animating = false;
+                    caps.animate.stop(INSTRUMENT_ANIMATION);
+                }
                 caps.render.render();
             }
-            InstrumentEV::NodeSnoopData(d) => {
-                let mut world = model.world.lock().expect("lock world");
-                for (f_n, d) in d {
-                    let d_max: f32 = *d
+            InstrumentEV::Clock(event) => model.clock.update(event),
+            InstrumentEV::NodeLevelsData(levels) => {
+                let world = model.world.lock().expect("lock world");
+                for level in levels {
+                    if let Some(mut node) = model
+                        .nodes
                         .iter()
-                        .max_by(|v, v1| {
-                            if v.abs() > v1.abs() {
-                                Ordering::Greater
-                            } else if v.abs() < v1.abs() {
-                                Ordering::Less
-                            } else {
-                                Ordering::Equal
-                            }
-                        })
-                        .unwrap_or(&0.0);
-                    let (_, node) = world
-                        .query_mut::<&mut Node>()
-                        .into_iter()
-                        .find(|(_, node)| node.f_n == f_n)
-                        .expect("node for f_n");
-                    node.triggered = d_max.sin();
+                        .filter_map(|e| world.get::<&mut Node>(*e).ok())
+                        .find(|node| node.f_n == level.f_n)
+                    {
+                        node.triggered = trigger_level(level.envelope);
+                    } else {
+                        log::warn!("no node for f_n {}", level.f_n);
+                    }
                 }
                 caps.render.render();
             }
+            InstrumentEV::LevelsData(levels) => {
+                model.levels = levels;
+                caps.render.render();
+            }
+            InstrumentEV::SpectrumData(spectrum) => {
+                model.spectrum = spectrum;
+                caps.render.render();
+            }
             InstrumentEV::None => {}
         }
     }
@@ -269,12 +572,17 @@ impl App for Instrument {
             config: model.config.clone(),
             layout: model.layout.clone().unwrap_or_default(),
             data_out: self.get_data_out(model),
+            master_bus: model.master_bus,
+            effects: model.effects,
+            levels: model.levels.clone(),
+            spectrum: model.spectrum.clone(),
+            tuning_error: model.tuning_error.clone(),
         }
     }
 }
 
 impl Instrument {
-    fn get_nodes(&self, model: &Model) -> Vec<Node> {
+    pub fn get_nodes(&self, model: &Model) -> Vec<Node> {
         let world = model.world.lock().expect("world lock");
         model
             .nodes
@@ -283,6 +591,36 @@ impl Instrument {
             .collect()
     }
 
+    fn update_node(
+        &self,
+        model: &mut Model,
+        caps: &InstrumentCapabilities,
+        f_n: usize,
+        f: impl FnOnce(&mut Node),
+    ) {
+        {
+            let world = model.world.lock().expect("world lock");
+            match model
+                .nodes
+                .iter()
+                .filter_map(|e| world.get::<&mut Node>(*e).ok())
+                .find(|node| node.f_n == f_n)
+            {
+                Some(mut node) => {
+                    f(&mut node);
+                    model.mixes.insert(f_n, node.mix());
+                }
+                None => log::warn!("no node for f_n {f_n}"),
+            }
+        }
+
+        if model.configured {
+            let nodes = self.get_nodes(model);
+            caps.play.node_gains(node_gains(nodes.as_slice()), InstrumentEV::PlayOpNodeGains);
+        }
+        caps.render.render();
+    }
+
     fn get_data_out(&self, model: &Model) -> Vec<Point2<f64>> {
         let world = model.world.lock().expect("world lock");
         model
@@ -298,3 +636,83 @@ impl Instrument {
             .unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crux_core::testing::AppTester;
+
+    use super::*;
+
+    #[test]
+    fn keeps_node_voices_across_reconfiguration() {
+        let app = AppTester::<Instrument, Effect>::default();
+        let mut model = Model::default();
+        let config = Config::new(1920.0, 1080.0, 96.0, Default::default());
+
+        _ = app.update(InstrumentEV::CreateWithConfig(config.clone()), &mut model);
+        _ = app.update(
+            InstrumentEV::SetVoice(Some(2), Voice::PluckedString),
+            &mut model,
+        );
+        _ = app.update(InstrumentEV::CreateWithConfig(config), &mut model);
+
+        let nodes = app.view(&model).nodes;
+        assert!(nodes.len() > 2);
+        for node in nodes {
+            let voice = if node.f_n == 2 {
+                Voice::PluckedString
+            } else {
+                Voice::SineBell
+            };
+            assert_eq!(node.voice, voice);
+        }
+
+        _ = app.update(InstrumentEV::SetVoice(None, Voice::SineBell), &mut model);
+        assert!(model.voices.is_empty());
+    }
+
+    #[test]
+    fn keeps_node_mixes_across_reconfiguration() {
+        let app = AppTester::<Instrument, Effect>::default();
+        let mut model = Model::default();
+
+        _ = app.update(
+            InstrumentEV::CreateWithConfig(Config::new(1920.0, 1080.0, 96.0, Default::default())),
+            &mut model,
+        );
+        model.configured = true;
+        _ = app.update(InstrumentEV::SetNodeMute(2, true), &mut model);
+        _ = app.update(
+            InstrumentEV::CreateWithConfig(Config::new(1080.0, 1920.0, 96.0, Default::default())),
+            &mut model,
+        );
+
+        let nodes = app.view(&model).nodes;
+        assert_eq!(nodes.len(), model.config.n_buttons);
+        for node in nodes.iter() {
+            assert_eq!(node.muted, node.f_n == 2, "{node:?}");
+        }
+
+        let update = app.update(InstrumentEV::SetNodeGain(3, -20.0), &mut model);
+        let gains = update
+            .effects
+            .into_iter()
+            .find_map(|effect| match effect {
+                Effect::Play(req) => match req.operation {
+                    PlayOperation::NodeGains(gains) => Some(gains),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .expect("node gains sent");
+        assert_eq!(gains.len(), nodes.len());
+        for (f_n, gain) in gains {
+            let expected = match f_n {
+                2 => 0.0,
+                3 => 0.1,
+                _ => 1.0,
+            };
+            assert!((gain - expected).abs() < 1e-6, "{f_n}: {gain}");
+        }
+    }
+}
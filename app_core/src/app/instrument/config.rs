@@ -1,6 +1,11 @@
 use std::collections::BTreeMap;
 use hecs::{Entity, World, Bundle};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::geometry::{Rect, Transform};
+
+use super::{Orientation, TuningSystem};
 
 const MIN_BUTTON_SIZE_IN: f64 = 0.75;
 const MAX_BUTTON_SIZE_B_RATIO: f64 = 0.6;
@@ -9,10 +14,78 @@ const BUTTON_SPACE_RATIO: f64 = 2.0;
 const DPI_RANGE: &[usize] = &[120, 160, 240, 320, 480, 640];
 const F_BASE: f64 = 110.0;
 const F_MAX: f64 = 5500.0;
+const SNOOP_POINTS_PER_IN: f64 = 24.0;
+const MIN_SNOOP_SIZE: usize = 32;
+const MAX_SNOOP_SIZE: usize = 512;
+const WRAP_ASPECT: f64 = 3.0;
+
+/// Screens no keyboard can be laid out on.
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("invalid dpi {0}")]
+    InvalidDpi(f64),
+    #[error("screen {width}x{height} is too small for the instrument")]
+    TooSmall { width: f64, height: f64 },
+    #[error("safe area {0:?} leaves no room on the screen")]
+    InvalidSafeArea([f64; 4]),
+    #[error("invalid hinge {0:?}")]
+    InvalidHinge(Rect),
+    #[error("no keyboard fits the screen")]
+    NoKeyboard,
+}
+
+/// Where the fundamental of the instrument comes from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum F0Mode {
+    /// Fixed fundamental in Hz, stays put when the screen changes.
+    Fixed(f32),
+    /// Derived from the screen area, follows rotation and resizing.
+    Screen,
+}
+
+impl Eq for F0Mode {}
+
+impl Default for F0Mode {
+    fn default() -> Self {
+        Self::Fixed(F_BASE as f32)
+    }
+}
+
+impl F0Mode {
+    pub fn resolve(&self, screen_f0: f32) -> f32 {
+        match *self {
+            Self::Fixed(f0) if f0 > 0.0 && f0.is_finite() => f0,
+            Self::Fixed(f0) => {
+                log::warn!("invalid f0 {f0}, using screen");
+                screen_f0
+            }
+            Self::Screen => screen_f0,
+        }
+    }
+}
+
+/// Which hand the keyboard is laid out for.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Handedness {
+    #[default]
+    Right,
+    /// Groups in reverse from the main end, tracks of a single row on the
+    /// same side and the menu in the opposite corner.
+    Left,
+}
+
+/// How the output is reduced to the points of the outbound string.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Decimation {
+    /// Latest `snoop_size` samples as they are.
+    Off,
+    /// Minimum and maximum of each window, keeps transients visible.
+    #[default]
+    MinMax,
+}
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Bundle)]
 pub struct Config {
-    pub portrait: bool,
     pub width: f64,
     pub height: f64,
     pub breadth: f64,
@@ -24,32 +97,163 @@ pub struct Config {
     pub button_size: f64,
     pub button_track_margin: f64,
     pub safe_area: [f64; 4],
+    pub rows: usize,
+    pub hinge: Option<Rect>,
+    pub orientation: Orientation,
+    pub handedness: Handedness,
     pub f0: f32,
+    pub tuning_system: TuningSystem,
+    pub f0_mode: F0Mode,
+    pub screen_f0: f32,
+    pub snoop_size: usize,
+    pub snoop_decimation: Decimation,
 }
 
 impl Eq for Config {}
 
 impl Config {
+    /// Layout for the screen, see [`Config::try_new`].
+    ///
+    /// # Panics
+    ///
+    /// If no keyboard fits the screen.
     pub fn new(width: f64, height: f64, dpi: f64, safe_area: [f64; 4]) -> Self {
-        let portrait = height > width;
+        Self::new_with_hinge(width, height, dpi, safe_area, None)
+    }
 
-        let (length, safe_length, safe_breadth) = if portrait {
-            (
-                height,
-                height - safe_area[1] - safe_area[3],
-                (width / 3.0).min(width - safe_area[0] - safe_area[2]),
-            )
+    /// Layout for a screen with a hinge, see [`Config::try_new_with_hinge`].
+    ///
+    /// # Panics
+    ///
+    /// If no keyboard fits the screen.
+    pub fn new_with_hinge(
+        width: f64,
+        height: f64,
+        dpi: f64,
+        safe_area: [f64; 4],
+        hinge: Option<Rect>,
+    ) -> Self {
+        Self::try_new_with_hinge(width, height, dpi, safe_area, hinge)
+            .unwrap_or_else(|e| panic!("{width}x{height} at {dpi} dpi: {e}"))
+    }
+
+    pub fn try_new(
+        width: f64,
+        height: f64,
+        dpi: f64,
+        safe_area: [f64; 4],
+    ) -> Result<Self, ConfigError> {
+        Self::try_new_with_hinge(width, height, dpi, safe_area, None)
+    }
+
+    /// Like [`Config::try_new`] for screens with a hinge or a fold, given as
+    /// the rect to keep clear of.
+    ///
+    /// A hinge across the keyboard splits it into two halves of the same
+    /// number of buttons. A hinge along it is kept clear of like a safe area,
+    /// leaving the keyboard on the larger side. Very wide screens without a
+    /// hinge wrap the keyboard into two rows.
+    pub fn try_new_with_hinge(
+        width: f64,
+        height: f64,
+        dpi: f64,
+        safe_area: [f64; 4],
+        hinge: Option<Rect>,
+    ) -> Result<Self, ConfigError> {
+        let orientation = Orientation::for_screen(width, height);
+        Self::try_new_oriented(width, height, dpi, safe_area, hinge, orientation)
+    }
+
+    /// Like [`Config::try_new_with_hinge`] with the keyboard rotated and
+    /// mirrored by `orientation` instead of following the screen.
+    pub fn try_new_oriented(
+        width: f64,
+        height: f64,
+        dpi: f64,
+        safe_area: [f64; 4],
+        hinge: Option<Rect>,
+        orientation: Orientation,
+    ) -> Result<Self, ConfigError> {
+        Self::try_new_tuned(
+            width,
+            height,
+            dpi,
+            safe_area,
+            hinge,
+            orientation,
+            F0Mode::default(),
+        )
+    }
+
+    /// Like [`Config::try_new_oriented`] with the fundamental taken from
+    /// `f0_mode`, the number of buttons keeps the top one below `F_MAX`.
+    pub fn try_new_tuned(
+        width: f64,
+        height: f64,
+        dpi: f64,
+        safe_area: [f64; 4],
+        hinge: Option<Rect>,
+        orientation: Orientation,
+        f0_mode: F0Mode,
+    ) -> Result<Self, ConfigError> {
+        if !(dpi.is_finite() && dpi > 0.0) {
+            return Err(ConfigError::InvalidDpi(dpi));
+        }
+
+        if !(width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0) {
+            return Err(ConfigError::TooSmall { width, height });
+        }
+
+        if safe_area.iter().any(|sa| !(sa.is_finite() && *sa >= 0.0))
+            || safe_area[0] + safe_area[2] >= width
+            || safe_area[1] + safe_area[3] >= height
+        {
+            return Err(ConfigError::InvalidSafeArea(safe_area));
+        }
+
+        if let Some(hinge) = hinge {
+            let (left, right, top, bottom) = hinge.components();
+            if ![left, right, top, bottom].iter().all(|c| c.is_finite()) {
+                return Err(ConfigError::InvalidHinge(hinge));
+            }
+        }
+
+        let (long, short) = orientation.extent(width, height);
+        let keyboard_area = orientation.to_keyboard(safe_area);
+        let keyboard_hinge = hinge.map(|h| orientation.rect_to_keyboard(&h, width, height));
+
+        let screen_breadth = (short / 3.0).min(short - keyboard_area[1] - keyboard_area[3]);
+
+        let split = keyboard_hinge.filter(splits);
+
+        let keyboard_area = match keyboard_hinge {
+            Some(hinge) if split.is_none() => fold(keyboard_area, &hinge, short),
+            _ => keyboard_area,
+        };
+
+        let rows = if split.is_none() && long / short >= WRAP_ASPECT {
+            2
         } else {
-            (
-                width,
-                width - safe_area[0] - safe_area[2],
-                (height / 3.0).min(height - safe_area[1] - safe_area[3]),
-            )
+            1
         };
 
+        let segments = segments(long, &keyboard_area, split.as_ref());
+        let strips = rows * segments.len();
+
+        let length = long / segments.len() as f64;
+        let safe_length = segments
+            .iter()
+            .fold(f64::INFINITY, |acc, (_, length)| acc.min(*length));
+        let safe_breadth =
+            (short / (rows + 2) as f64).min(short - keyboard_area[1] - keyboard_area[3]);
+
         let max_button_size = (safe_breadth * MAX_BUTTON_SIZE_B_RATIO).round() as usize;
         let min_button_size = f64::sqrt(dpi * MIN_BUTTON_SIZE_IN).round() as usize;
 
+        if max_button_size < min_button_size.max(1) {
+            return Err(ConfigError::TooSmall { width, height });
+        }
+
         let (min_groups, min_buttons) = {
             let max_buttons = ((length - max_button_size as f64 * BUTTON_SPACE_RATIO)
                 / max_button_size as f64)
@@ -74,9 +278,9 @@ impl Config {
 
         let min_count = min_groups * min_buttons;
 
-        let f_c = (F_BASE / f64::sqrt((length * safe_breadth) / dpi)) as f32;
+        let f_c = (F_BASE / f64::sqrt((long * screen_breadth) / dpi)) as f32;
 
-        let f0 = {
+        let screen_f0 = {
             let mut f0 = f_c;
 
             while f0 < F_BASE as f32 {
@@ -85,6 +289,7 @@ impl Config {
 
             f0
         };
+        let f0 = f0_mode.resolve(screen_f0);
 
         let mut candidates = Vec::<(usize, usize, usize, usize)>::new();
 
@@ -99,7 +304,7 @@ impl Config {
             ] {
                 let count = groups * buttons_group;
                 let used_space = space * count as f64;
-                let f_max = f0 as f64 * 2.0 * (groups * buttons_group) as f64;
+                let f_max = f0 as f64 * 2.0 * (count * strips) as f64;
                 if count >= min_count && used_space < safe_length && f_max <= F_MAX {
                     candidates.push((size, groups, buttons_group, active_length));
                 }
@@ -120,10 +325,10 @@ impl Config {
         );
 
         let (d_size, d_groups, d_buttons_group, d_active_length, d_count) = (
-            (d_size.1 - d_size.0) as f64,
-            (d_groups.1 - d_groups.0) as f64,
-            (d_buttons_group.1 - d_buttons_group.0) as f64,
-            (d_active_length.1 - d_active_length.0) as f64,
+            (d_size.1 - d_size.0).max(1) as f64,
+            (d_groups.1 - d_groups.0).max(1) as f64,
+            (d_buttons_group.1 - d_buttons_group.0).max(1) as f64,
+            (d_active_length.1 - d_active_length.0).max(1) as f64,
             (d_count.1 - d_count.0).max(1) as f64,
         );
 
@@ -175,8 +380,20 @@ impl Config {
 
         let whitespace = (safe_length - active_length) / 2.0;
 
-        Config {
-            portrait,
+        let snoop_size = {
+            let points = (active_length / dpi * SNOOP_POINTS_PER_IN).round() as usize;
+            let points = points.clamp(MIN_SNOOP_SIZE, MAX_SNOOP_SIZE);
+            points + points % 2
+        };
+
+        let strip_buttons = groups * buttons_group;
+        if strip_buttons == 0 || active_length / (strip_buttons as f64) < button_size {
+            return Err(ConfigError::NoKeyboard);
+        }
+
+        let groups = groups * strips;
+
+        Ok(Config {
             width,
             height,
             length: active_length,
@@ -186,20 +403,156 @@ impl Config {
             buttons_group,
             n_buttons: buttons_group * groups,
             button_track_margin: BUTTON_TRACK_MARGIN_RATION,
-            safe_area,
+            safe_area: orientation.to_screen(keyboard_area),
+            rows,
+            hinge,
+            orientation,
+            handedness: Handedness::default(),
             whitespace,
             f0,
+            tuning_system: TuningSystem::default(),
+            f0_mode,
+            screen_f0,
+            snoop_size,
+            snoop_decimation: Decimation::default(),
+        })
+    }
+
+    /// Same layout for the `handedness` player.
+    pub fn with_handedness(self, handedness: Handedness) -> Self {
+        Self { handedness, ..self }
+    }
+
+    /// Same layout with buttons tuned to `tuning_system`.
+    pub fn with_tuning_system(self, tuning_system: TuningSystem) -> Self {
+        Self {
+            tuning_system,
+            ..self
+        }
+    }
+
+    /// Whether the keyboard is a single strip across the screen.
+    pub fn is_single_strip(&self) -> bool {
+        self.rows <= 1 && self.hinge.is_none()
+    }
+
+    /// Main and cross axis extents of the screen.
+    pub fn extent(&self) -> (f64, f64) {
+        self.orientation.extent(self.width, self.height)
+    }
+
+    /// Safe area as `[main start, cross start, main end, cross end]`.
+    pub fn keyboard_area(&self) -> [f64; 4] {
+        self.orientation.to_keyboard(self.safe_area)
+    }
+
+    /// Cross axis range free of the safe area.
+    pub fn cross_range(&self) -> (f64, f64) {
+        let (_, short) = self.extent();
+        let area = self.keyboard_area();
+        (area[1], short - area[3])
+    }
+
+    /// From keyboard coordinates, main axis along x and cross axis
+    /// along y, to the screen.
+    pub fn transform(&self) -> Transform {
+        self.orientation.transform(self.width, self.height)
+    }
+
+    /// Screen `rect` in keyboard coordinates.
+    pub fn rect_to_keyboard(&self, rect: &Rect) -> Rect {
+        self.orientation
+            .rect_to_keyboard(rect, self.width, self.height)
+    }
+
+    /// Main axis ranges of the keyboard as `(start, length)`, two when
+    /// a hinge splits it.
+    pub fn segments(&self) -> Vec<(f64, f64)> {
+        let (long, _) = self.extent();
+        let split = self.keyboard_hinge().filter(splits);
+        segments(long, &self.keyboard_area(), split.as_ref())
+    }
+
+    /// Cross axis position where the row `row` of buttons starts,
+    /// `row_start(rows)` is where the last row ends.
+    pub fn row_start(&self, row: usize) -> f64 {
+        if self.is_single_strip() {
+            return self.breadth * (row + 1) as f64;
+        }
+
+        let (start, end) = self.cross_range();
+
+        start + (end - start - self.breadth * self.rows as f64) / 2.0 + self.breadth * row as f64
+    }
+
+    /// Cross axis range next to a hinge along the keyboard, on its smaller side.
+    pub fn fold_side(&self) -> Option<(f64, f64)> {
+        let hinge = self.keyboard_hinge().filter(|h| !splits(h))?;
+        let (_, short) = self.extent();
+        let (_, _, c0, c1) = hinge.components();
+
+        if c0 + c1 < short {
+            Some(((c0 - self.breadth).max(0.0), c0))
+        } else {
+            Some((c1, (c1 + self.breadth).min(short)))
         }
     }
 
     pub fn spawn(&self, world: &mut World) -> Entity {
         world.spawn((self.clone(),))
     }
+
+    fn keyboard_hinge(&self) -> Option<Rect> {
+        self.hinge.map(|h| self.rect_to_keyboard(&h))
+    }
+}
+
+/// Whether the hinge, in keyboard space, runs across the main axis.
+fn splits(hinge: &Rect) -> bool {
+    hinge.height() >= hinge.width()
+}
+
+/// Grows the keyboard space safe area over the smaller side of a hinge
+/// along the main axis.
+fn fold(mut keyboard_area: [f64; 4], hinge: &Rect, short: f64) -> [f64; 4] {
+    let (_, _, c0, c1) = hinge.components();
+
+    if c0 + c1 < short {
+        keyboard_area[1] = keyboard_area[1].max(c1);
+    } else {
+        keyboard_area[3] = keyboard_area[3].max(short - c0);
+    }
+
+    keyboard_area
+}
+
+fn segments(long: f64, keyboard_area: &[f64; 4], split: Option<&Rect>) -> Vec<(f64, f64)> {
+    let (start, length) = (keyboard_area[0], long - keyboard_area[0] - keyboard_area[2]);
+
+    let Some(hinge) = split else {
+        return vec![(start, length)];
+    };
+
+    let (h0, h1, _, _) = hinge.components();
+    let end = start + length;
+    let (h0, h1) = (h0.clamp(start, end), h1.clamp(start, end));
+
+    let halves = [(start, h0 - start), (h1, end - h1)]
+        .into_iter()
+        .filter(|(_, length)| *length > 0.0)
+        .collect::<Vec<_>>();
+
+    if halves.is_empty() {
+        vec![(start, length)]
+    } else {
+        halves
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const RAND_SCREENS: &[(f64, f64, f64)] = &[
         (1920.0, 1080.0, 96.0),
@@ -245,4 +598,124 @@ mod tests {
 
         insta::assert_yaml_snapshot!(configs)
     }
+
+    fn rotated_configs(f0_mode: F0Mode) -> Vec<Config> {
+        [
+            (1920.0, 1080.0, 96.0),
+            (1920.0, 900.0, 96.0),
+            (2436.0, 1125.0, 458.0),
+        ]
+        .into_iter()
+        .flat_map(|(width, height, dpi)| [(width, height, dpi), (height, width, dpi)])
+        .map(|(width, height, dpi)| {
+            Config::try_new_tuned(
+                width,
+                height,
+                dpi,
+                [50.0, 20.0, 10.0, 25.0],
+                None,
+                Orientation::for_screen(width, height),
+                f0_mode,
+            )
+            .unwrap()
+        })
+        .collect()
+    }
+
+    fn pitches(configs: &[Config]) -> Vec<String> {
+        configs
+            .iter()
+            .map(|config| {
+                let (f1, f1_max) = config.tuning_system.band(config.f0, 1);
+                let (f2, f2_max) = config.tuning_system.band(config.f0, 2);
+                format!(
+                    "{}x{} f0 {} f1 {f1} to {f1_max} f2 {f2} to {f2_max}",
+                    config.width, config.height, config.f0
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pitch_snapshot_by_rotation_and_resize() {
+        let configs = rotated_configs(F0Mode::Screen);
+
+        assert_ne!(configs[0].screen_f0, configs[2].screen_f0);
+        for config in configs.iter() {
+            assert_eq!(config.f0, config.screen_f0);
+        }
+
+        insta::assert_yaml_snapshot!(pitches(&configs))
+    }
+
+    #[test]
+    fn fixed_pitch_snapshot_by_rotation_and_resize() {
+        let configs = rotated_configs(F0Mode::default());
+
+        for (config, screen) in configs.iter().zip(rotated_configs(F0Mode::Screen)) {
+            assert_eq!(config.f0, 110.0);
+            assert_eq!(config.f0_mode, F0Mode::Fixed(110.0));
+            assert_eq!(config.screen_f0, screen.screen_f0);
+        }
+
+        insta::assert_yaml_snapshot!(pitches(&configs))
+    }
+
+    #[test]
+    fn rejects_degenerate_screens() {
+        assert_eq!(
+            Config::try_new(1920.0, 1080.0, 0.0, Default::default()),
+            Err(ConfigError::InvalidDpi(0.0))
+        );
+        assert_eq!(
+            Config::try_new(40.0, 30.0, 480.0, Default::default()),
+            Err(ConfigError::TooSmall {
+                width: 40.0,
+                height: 30.0
+            })
+        );
+        assert_eq!(
+            Config::try_new(1920.0, 1080.0, 96.0, [1000.0, 0.0, 920.0, 0.0]),
+            Err(ConfigError::InvalidSafeArea([1000.0, 0.0, 920.0, 0.0]))
+        );
+        assert_eq!(
+            Config::try_new(1920.0, 1080.0, 96.0, [0.0, f64::NAN, 0.0, 0.0]).ok(),
+            None
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn any_screen_is_a_config_or_an_error(
+            width in -100.0..8000.0_f64,
+            height in -100.0..8000.0_f64,
+            dpi in -10.0..800.0_f64,
+            safe_area in prop::array::uniform4(0.0..600.0_f64),
+        ) {
+            if let Ok(config) = Config::try_new(width, height, dpi, safe_area) {
+                prop_assert!(config.n_buttons > 0);
+                prop_assert_eq!(config.n_buttons, config.groups * config.buttons_group);
+                prop_assert!(config.button_size > 0.0 && config.button_size <= config.breadth);
+                prop_assert!(config.length.is_finite() && config.length >= config.button_size);
+                prop_assert!(config.f0.is_finite() && config.f0 > 0.0);
+            }
+        }
+
+        #[test]
+        fn common_screens_fit_a_keyboard(
+            short in 320.0..2400.0_f64,
+            aspect in 1.0..2.5_f64,
+            dpi in 96.0..640.0_f64,
+            safe_area in prop::array::uniform4(0.0..48.0_f64),
+            portrait: bool,
+        ) {
+            let (width, height) = if portrait {
+                (short, short * aspect)
+            } else {
+                (short * aspect, short)
+            };
+            let config = Config::try_new(width, height, dpi, safe_area);
+            prop_assert!(config.is_ok(), "{width}x{height} at {dpi}: {config:?}");
+        }
+    }
 }
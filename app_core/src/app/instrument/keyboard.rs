@@ -1,4 +1,4 @@
-use super::config::Config;
+use super::config::{Config, Handedness};
 use crate::{geometry::Rect, tuner::TriggerState};
 use hecs::{Bundle, Entity, World};
 
@@ -22,28 +22,21 @@ impl Track {
 
         let track_length = config.breadth * 2.0 + button_track_margin + config.button_size;
 
-        let rect = if config.portrait {
-            button_rect
-                .offset_top(button_track_margin)
-                .offset_bottom(button_track_margin)
-        } else {
-            button_rect
-                .offset_left(button_track_margin)
-                .offset_right(button_track_margin)
-        };
+        // left hand tracks reach the cross start, left-handed strips keep
+        // all tracks on that side
+        let reach_start = left_hand || (config.handedness == Handedness::Left && config.rows <= 1);
 
-        let rect = if left_hand {
-            if config.portrait {
-                rect.offset_left_and_right(track_length, button_track_margin)
-            } else {
-                rect.offset_top_and_bottom(button_track_margin, track_length)
-            }
-        } else if config.portrait {
-            rect.offset_left_and_right(button_track_margin, track_length)
+        let (before, after) = if reach_start {
+            (track_length, button_track_margin)
         } else {
-            rect.offset_top_and_bottom(track_length, button_track_margin)
+            (button_track_margin, track_length)
         };
 
+        let rect = button_rect
+            .offset_left_and_right(button_track_margin, button_track_margin)
+            .offset_top_and_bottom(before, after);
+        let rect = config.transform().apply_rect(&rect);
+
         let freq = (base_freq, max_freq);
 
         world.spawn((Self {
@@ -66,38 +59,40 @@ pub struct Button {
 
 impl Button {
     pub fn spawn(world: &mut World, config: &Config, group: usize, button: usize) -> Entity {
+        let (at_group, at_button) = placement(config, group, button);
+        let (row, (start, length), groups_strip) = strip(config, at_group);
+        let buttons_strip = groups_strip * config.buttons_group;
+
         let button_space_side = (config.breadth - config.button_size) / 2.0;
-        let button_space_main = (config.length / (config.groups * config.buttons_group) as f64
-            - config.button_size)
-            / 2.0;
+        let button_space_main = (config.length / buttons_strip as f64 - config.button_size) / 2.0;
         let total_buttons = config.n_buttons;
         let idx = (group - 1) * config.buttons_group + (button - 1);
+        let strip_idx = ((at_group - 1) * config.buttons_group + (at_button - 1)) % buttons_strip;
 
-        let side = config.breadth + button_space_side;
+        let side = config.row_start(row) + button_space_side;
         let side_breadth = side + config.button_size;
 
-        let offset = if config.portrait {
-            config.safe_area[1]
-        } else {
-            config.safe_area[0]
-        } + config.whitespace;
+        let offset = start + (length - config.length) / 2.0;
 
         let main = offset
-            + (config.button_size + button_space_main * 2.0) * idx as f64
+            + (config.button_size + button_space_main * 2.0) * strip_idx as f64
             + button_space_main;
         let main_length = main + config.button_size;
 
-        let rect = if config.portrait {
-            Rect::new(side, side_breadth, main, main_length)
-        } else {
-            Rect::new(main, main_length, side, side_breadth)
-        };
+        let keyboard_rect = Rect::new(main, main_length, side, side_breadth);
+        let rect = config.transform().apply_rect(&keyboard_rect);
 
         let f_n = total_buttons - idx;
-        let freq = config.f0 * (f_n * 2) as f32 - config.f0;
-        let max_freq = freq + config.f0;
+        let (freq, max_freq) = config.tuning_system.band(config.f0, f_n);
+
+        // rows keep their tracks on the outer side, away from each other
+        let left_hand = if config.rows > 1 {
+            row == 0
+        } else {
+            group % 2 == 0
+        };
 
-        let track = Track::spawn(world, config, group % 2 == 0, &rect, freq, max_freq);
+        let track = Track::spawn(world, config, left_hand, &keyboard_rect, freq, max_freq);
 
         world.spawn((Button {
             rect,
@@ -122,22 +117,19 @@ impl ButtonGroup {
         for j in 1..=config.buttons_group {
             buttons.push(Button::spawn(world, config, group, j));
         }
-        let group_length = config.length / config.groups as f64;
-        let rect = if config.portrait {
-            Rect::new(
-                config.breadth,
-                config.breadth * 2.0,
-                group_length * (group - 1) as f64 + config.safe_area[1] + config.whitespace,
-                group_length * group as f64,
-            )
-        } else {
-            Rect::new(
-                group_length * (group - 1) as f64 + config.safe_area[0] + config.whitespace,
-                group_length * group as f64,
-                config.breadth,
-                config.breadth * 2.0,
-            )
-        };
+        let (at_group, _) = placement(config, group, 1);
+        let (row, (start, length), groups_strip) = strip(config, at_group);
+        let group_length = config.length / groups_strip as f64;
+        let main = start
+            + (length - config.length) / 2.0
+            + group_length * ((at_group - 1) % groups_strip) as f64;
+        let side = config.row_start(row);
+        let rect = config.transform().apply_rect(&Rect::new(
+            main,
+            main + group_length,
+            side,
+            side + config.breadth,
+        ));
 
         world.spawn((Self { buttons, rect },))
     }
@@ -158,4 +150,49 @@ impl Keyboard {
         let rect = Rect::size(config.width, config.height);
         world.spawn((Keyboard { groups, rect },))
     }
+
+    /// Despawns the keyboard with its groups, buttons and their tracks.
+    pub fn despawn(world: &mut World, keyboard: Entity) {
+        let groups = world
+            .get::<&Keyboard>(keyboard)
+            .map(|k| k.groups.clone())
+            .unwrap_or_default();
+        for group in groups {
+            let buttons = world
+                .get::<&ButtonGroup>(group)
+                .map(|g| g.buttons.clone())
+                .unwrap_or_default();
+            for button in buttons {
+                let track = world.get::<&Button>(button).map(|b| b.track);
+                if let Ok(track) = track {
+                    _ = world.despawn(track);
+                }
+                _ = world.despawn(button);
+            }
+            _ = world.despawn(group);
+        }
+        _ = world.despawn(keyboard);
+    }
+}
+
+/// Group and button whose place `group` and `button` take, left-handed
+/// keyboards run from the other end.
+fn placement(config: &Config, group: usize, button: usize) -> (usize, usize) {
+    match config.handedness {
+        Handedness::Right => (group, button),
+        Handedness::Left => (config.groups + 1 - group, config.buttons_group + 1 - button),
+    }
+}
+
+/// Row, main axis segment and number of groups of the strip holding `group`.
+fn strip(config: &Config, group: usize) -> (usize, (f64, f64), usize) {
+    let segments = config.segments();
+    let groups_strip = (config.groups / (config.rows.max(1) * segments.len())).max(1);
+    let strip = (group - 1) / groups_strip;
+
+    (
+        strip / segments.len(),
+        segments[strip % segments.len()],
+        groups_strip,
+    )
 }
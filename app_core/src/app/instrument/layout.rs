@@ -1,14 +1,15 @@
 use anyhow::Result;
 use hecs::{Bundle, Entity, World};
 use keyframe::CanTween;
+use mint::Point2;
 use serde::{Deserialize, Serialize};
 
-use crate::geometry::{line::Line, rect::Rect};
+use crate::geometry::{line::Line, rect::Rect, SpatialIndex};
 
 use super::{
     keyboard::{Button, ButtonGroup, Keyboard, Track},
     string::{InboundString, OutboundString},
-    Config,
+    Config, Handedness,
 };
 
 #[derive(Default, Serialize, Deserialize, Clone, PartialEq, Debug, Eq)]
@@ -25,6 +26,7 @@ pub enum MenuPosition {
     TopLeft(Rect),
     TopRight(Rect),
     BottomLeft(Rect),
+    BottomRight(Rect),
     Center(Rect),
 }
 
@@ -40,7 +42,8 @@ impl MenuPosition {
             MenuPosition::Center(r)
             | MenuPosition::TopLeft(r)
             | MenuPosition::TopRight(r)
-            | MenuPosition::BottomLeft(r) => r,
+            | MenuPosition::BottomLeft(r)
+            | MenuPosition::BottomRight(r) => r,
         }
     }
 }
@@ -54,10 +57,18 @@ impl CanTween for MenuPosition {
             MenuPosition::Center(r) => MenuPosition::Center(CanTween::ease(r1, r, time)),
             MenuPosition::TopRight(r) => MenuPosition::TopRight(CanTween::ease(r1, r, time)),
             MenuPosition::BottomLeft(r) => MenuPosition::BottomLeft(CanTween::ease(r1, r, time)),
+            MenuPosition::BottomRight(r) => MenuPosition::BottomRight(CanTween::ease(r1, r, time)),
         }
     }
 }
 
+/// What a pointer can land on, by position in the layout vectors.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum LayoutTarget {
+    Button(usize),
+    Track(usize),
+}
+
 #[derive(Bundle)]
 pub struct LayoutRoot {
     entities: (Entity, Entity, Entity),
@@ -130,42 +141,11 @@ impl Layout {
 
         log::debug!("tracks {tracks:#?}");
 
-        let menu_position = tracks
-            .first()
-            .map(|t| {
-                if config.portrait {
-                    if t.top_left().x >= config.breadth {
-                        MenuPosition::TopLeft(Rect::new(
-                            config.safe_area[0],
-                            config.breadth,
-                            config.safe_area[1],
-                            config.breadth,
-                        ))
-                    } else {
-                        MenuPosition::TopRight(Rect::new(
-                            config.width - config.breadth,
-                            config.width - config.safe_area[2],
-                            config.safe_area[1],
-                            config.breadth,
-                        ))
-                    }
-                } else if t.top_left().y < config.breadth {
-                    MenuPosition::BottomLeft(Rect::new(
-                        config.safe_area[0],
-                        config.breadth,
-                        config.height - config.breadth,
-                        config.height - config.safe_area[3],
-                    ))
-                } else {
-                    MenuPosition::TopLeft(Rect::new(
-                        config.safe_area[0],
-                        config.breadth,
-                        config.safe_area[1],
-                        config.breadth,
-                    ))
-                }
-            })
-            .unwrap_or_default();
+        let menu_position = match (config.fold_side(), tracks.first()) {
+            (Some((c0, c1)), _) => fold_menu(config, c0, c1),
+            (None, Some(t)) => strip_menu(config, t),
+            (None, None) => MenuPosition::default(),
+        };
 
         Ok(Self {
             inbound: inbound.line,
@@ -176,6 +156,22 @@ impl Layout {
         })
     }
 
+    /// Buttons and tracks for hit-testing, buttons come after their tracks.
+    pub fn index(&self) -> SpatialIndex<LayoutTarget> {
+        let cell = self
+            .buttons
+            .first()
+            .map_or(0.0, |b| b.width().max(b.height()));
+        let mut index = SpatialIndex::new(cell);
+        for (i, track) in self.tracks.iter().enumerate() {
+            index.insert(LayoutTarget::Track(i), *track);
+        }
+        for (i, button) in self.buttons.iter().enumerate() {
+            index.insert(LayoutTarget::Button(i), *button);
+        }
+        index
+    }
+
     pub fn dummy(breadth: f64, length: f64, button_size: f64) -> Self {
         let inbound = Line::new(0.0, length, 0.0, 1.0);
         let outbound = Line::new(0.0, length, breadth, breadth + 1.0);
@@ -191,6 +187,63 @@ impl Layout {
     }
 }
 
+/// Menu next to the keyboard, on the side its first track doesn't reach.
+fn strip_menu(config: &Config, t: &Rect) -> MenuPosition {
+    let (_, short) = config.extent();
+    let area = config.keyboard_area();
+    let cross_start = config.rect_to_keyboard(t).top_left().y >= config.breadth;
+    let (c0, c1) = if cross_start {
+        (area[1], config.breadth)
+    } else {
+        (short - config.breadth, short - area[3])
+    };
+
+    let rect = Rect::new(area[0], config.breadth, c0, c1);
+
+    menu_at(config, rect, cross_start)
+}
+
+/// Menu on the other side of a hinge along the keyboard.
+fn fold_menu(config: &Config, c0: f64, c1: f64) -> MenuPosition {
+    let rect = Rect::new(config.keyboard_area()[0], config.breadth, c0, c1);
+
+    menu_at(config, rect, c1 <= config.row_start(0))
+}
+
+/// Menu at `rect` in keyboard space, named after the screen corner where
+/// the keyboard starts on the cross start or end side.
+///
+/// Left-handed keyboards start from the main end, `rect` is mirrored over
+/// the safe main range.
+fn menu_at(config: &Config, rect: Rect, cross_start: bool) -> MenuPosition {
+    let (long, short) = config.extent();
+    let (rect, main) = match config.handedness {
+        Handedness::Right => (rect, 0.0),
+        Handedness::Left => {
+            let area = config.keyboard_area();
+            let mirror = area[0] + long - area[2];
+            let (m0, m1, c0, c1) = rect.components();
+            (Rect::new(mirror - m1, mirror - m0, c0, c1), long)
+        }
+    };
+
+    let to_screen = config.transform();
+    let corner = to_screen.apply(Point2 {
+        x: main,
+        y: if cross_start { 0.0 } else { short },
+    });
+    let left = corner.x < config.width / 2.0;
+    let top = corner.y < config.height / 2.0;
+    let rect = to_screen.apply_rect(&rect);
+
+    match (left, top) {
+        (true, true) => MenuPosition::TopLeft(rect),
+        (false, true) => MenuPosition::TopRight(rect),
+        (true, false) => MenuPosition::BottomLeft(rect),
+        (false, false) => MenuPosition::BottomRight(rect),
+    }
+}
+
 fn ease_vec(from: Vec<Rect>, to: Vec<Rect>, time: impl keyframe::num_traits::Float) -> Vec<Rect> {
     let len = CanTween::ease(from.len() as f64, to.len() as f64, time).round() as usize;
     (0..len)
@@ -1,7 +1,10 @@
 use hecs::{Entity, World};
 use serde::{Deserialize, Serialize};
 
-use super::keyboard::{Button, Track};
+use super::{
+    keyboard::{Button, Track},
+    Voice,
+};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Copy, Debug)]
 
@@ -10,18 +13,84 @@ pub struct Node {
     pub f_n: usize,
     pub pan: i8,
     pub triggered: f32,
+    pub voice: Voice,
+    pub muted: bool,
+    pub solo: bool,
+    pub gain_db: f32,
 }
 
 impl Eq for Node {}
 
+/// Mute, solo and gain set on a node, kept across layouts by `f_n`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Copy, Debug)]
+pub struct NodeMix {
+    pub muted: bool,
+    pub solo: bool,
+    pub gain_db: f32,
+}
+
+const TRIGGER_FLOOR_DB: f32 = -48.0;
+pub const MIN_GAIN_DB: f32 = -60.0;
+pub const MAX_GAIN_DB: f32 = 12.0;
+
+/// Maps a node envelope onto 0..1 on a dB scale.
+pub fn trigger_level(envelope: f32) -> f32 {
+    let db = 20.0 * envelope.max(f32::EPSILON).log10();
+    ((db - TRIGGER_FLOOR_DB) / -TRIGGER_FLOOR_DB).clamp(0.0, 1.0)
+}
+
 impl Node {
-    pub fn spawn(world: &mut World, freq: (f32, f32), f_n: usize, pan: i8) -> Entity {
+    pub fn spawn(
+        world: &mut World,
+        freq: (f32, f32),
+        f_n: usize,
+        pan: i8,
+        voice: Voice,
+    ) -> Entity {
         log::debug!("node pan: {pan}");
-        world.spawn((Self { freq, f_n, pan, triggered: 0.0 },))
+        world.spawn((Self {
+            freq,
+            f_n,
+            pan,
+            triggered: 0.0,
+            voice,
+            muted: false,
+            solo: false,
+            gain_db: 0.0,
+        },))
     }
+
+    pub fn mix(&self) -> NodeMix {
+        NodeMix {
+            muted: self.muted,
+            solo: self.solo,
+            gain_db: self.gain_db,
+        }
+    }
+
+    pub fn set_mix(&mut self, mix: NodeMix) {
+        self.muted = mix.muted;
+        self.solo = mix.solo;
+        self.gain_db = mix.gain_db;
+    }
+
+    /// Linear output gain, silent when muted or when another node is soloed.
+    pub fn gate(&self, any_solo: bool) -> f32 {
+        if self.muted || (any_solo && !self.solo) {
+            0.0
+        } else {
+            10_f32.powf(self.gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB) / 20.0)
+        }
+    }
+}
+
+/// Output gain of each node by `f_n`.
+pub fn node_gains(nodes: &[Node]) -> Vec<(usize, f32)> {
+    let any_solo = nodes.iter().any(|n| n.solo);
+    nodes.iter().map(|n| (n.f_n, n.gate(any_solo))).collect()
 }
 
-pub fn spawn_all_nodes(world: &mut World) -> Vec<Entity> {
+pub fn spawn_all_nodes(world: &mut World, voice: Voice) -> Vec<Entity> {
     let mut nodes = world
         .query::<&Button>()
         .iter()
@@ -37,6 +106,60 @@ pub fn spawn_all_nodes(world: &mut World) -> Vec<Entity> {
 
     nodes
         .into_iter()
-        .map(|(freq, f_n, pan)| Node::spawn(world, freq, f_n, pan))
+        .map(|(freq, f_n, pan)| Node::spawn(world, freq, f_n, pan, voice))
         .collect::<Vec<_>>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gates_muted_and_unsoloed_nodes() {
+        let node = Node {
+            freq: (110.0, 220.0),
+            f_n: 1,
+            pan: 1,
+            triggered: 0.0,
+            voice: Voice::default(),
+            muted: false,
+            solo: false,
+            gain_db: 0.0,
+        };
+        let nodes = vec![
+            node,
+            Node {
+                f_n: 2,
+                muted: true,
+                ..node
+            },
+            Node {
+                f_n: 3,
+                gain_db: -20.0,
+                ..node
+            },
+        ];
+
+        let gains = node_gains(&nodes);
+        assert_eq!(
+            gains.iter().map(|(f_n, _)| *f_n).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        for ((_, gain), expected) in gains.iter().zip([1.0, 0.0, 0.1]) {
+            assert!((gain - expected).abs() < 1e-6, "{gains:?}");
+        }
+
+        let nodes = vec![
+            Node { solo: true, ..node },
+            Node { f_n: 2, ..node },
+            Node {
+                f_n: 3,
+                solo: true,
+                muted: true,
+                ..node
+            },
+        ];
+
+        assert_eq!(node_gains(&nodes), vec![(1, 1.0), (2, 0.0), (3, 0.0)]);
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::{Rect, Transform};
+
+/// Which way the keyboard runs across the screen, from where it starts.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Left to right, strings along the top and bottom.
+    #[default]
+    Landscape,
+    /// Top to bottom, strings along the left and right.
+    Portrait,
+    /// Right to left, landscape turned upside down.
+    ReverseLandscape,
+    /// Bottom to top, portrait turned upside down.
+    ReversePortrait,
+}
+
+impl Rotation {
+    /// Follows the screen, portrait when it's taller than wide.
+    pub fn for_screen(width: f64, height: f64) -> Self {
+        if height > width {
+            Self::Portrait
+        } else {
+            Self::Landscape
+        }
+    }
+
+    /// Whether the main axis runs along the height of the screen.
+    pub fn is_portrait(&self) -> bool {
+        matches!(self, Self::Portrait | Self::ReversePortrait)
+    }
+
+    /// Screen sides in `[left, top, right, bottom]` order the keyboard
+    /// sides `[main start, cross start, main end, cross end]` are on.
+    fn sides(&self) -> [usize; 4] {
+        match self {
+            Self::Landscape => [0, 1, 2, 3],
+            Self::Portrait => [1, 0, 3, 2],
+            Self::ReverseLandscape => [2, 3, 0, 1],
+            Self::ReversePortrait => [3, 2, 1, 0],
+        }
+    }
+}
+
+/// How the keyboard is projected on the screen.
+///
+/// Layout is computed once in keyboard space, the main axis along x from
+/// `0` to the long side and the cross axis along y from `0` to the short
+/// side, then rotated and mirrored onto the screen.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Orientation {
+    pub rotation: Rotation,
+    /// Mirrored left to right on the screen, after the rotation.
+    pub mirrored: bool,
+}
+
+impl Orientation {
+    pub fn new(rotation: Rotation, mirrored: bool) -> Self {
+        Self { rotation, mirrored }
+    }
+
+    pub fn for_screen(width: f64, height: f64) -> Self {
+        Self::new(Rotation::for_screen(width, height), false)
+    }
+
+    pub fn is_portrait(&self) -> bool {
+        self.rotation.is_portrait()
+    }
+
+    /// Main and cross axis extents of a `width` by `height` screen.
+    pub fn extent(&self, width: f64, height: f64) -> (f64, f64) {
+        if self.is_portrait() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Screen safe area, `[left, top, right, bottom]`, as
+    /// `[main start, cross start, main end, cross end]` of the keyboard.
+    pub fn to_keyboard(&self, safe_area: [f64; 4]) -> [f64; 4] {
+        self.sides().map(|side| safe_area[side])
+    }
+
+    /// Inverse of [`Orientation::to_keyboard`].
+    pub fn to_screen(&self, keyboard_area: [f64; 4]) -> [f64; 4] {
+        let mut safe_area = [0.0; 4];
+        for (side, value) in self.sides().into_iter().zip(keyboard_area) {
+            safe_area[side] = value;
+        }
+        safe_area
+    }
+
+    /// From keyboard space to a `width` by `height` screen.
+    pub fn transform(&self, width: f64, height: f64) -> Transform {
+        let rotation = match self.rotation {
+            Rotation::Landscape => Transform::IDENTITY,
+            Rotation::Portrait => Transform::transpose(),
+            Rotation::ReverseLandscape => Transform::new(-1.0, 0.0, 0.0, -1.0, width, height),
+            Rotation::ReversePortrait => Transform::new(0.0, -1.0, -1.0, 0.0, width, height),
+        };
+
+        if self.mirrored {
+            rotation.then(&Transform::new(-1.0, 0.0, 0.0, 1.0, width, 0.0))
+        } else {
+            rotation
+        }
+    }
+
+    /// `rect` on a `width` by `height` screen in keyboard space.
+    pub fn rect_to_keyboard(&self, rect: &Rect, width: f64, height: f64) -> Rect {
+        self.transform(width, height)
+            .inverse()
+            .map_or(*rect, |to_keyboard| to_keyboard.apply_rect(rect))
+    }
+
+    fn sides(&self) -> [usize; 4] {
+        let sides = self.rotation.sides();
+        if self.mirrored {
+            sides.map(|side| [2, 1, 0, 3][side])
+        } else {
+            sides
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROTATIONS: [Rotation; 4] = [
+        Rotation::Landscape,
+        Rotation::Portrait,
+        Rotation::ReverseLandscape,
+        Rotation::ReversePortrait,
+    ];
+
+    #[test]
+    fn safe_area_follows_transform() {
+        let (width, height) = (800.0, 600.0);
+        let safe_area = [10.0, 20.0, 30.0, 40.0];
+
+        for rotation in ROTATIONS {
+            for mirrored in [false, true] {
+                let orientation = Orientation::new(rotation, mirrored);
+                let (long, short) = orientation.extent(width, height);
+                let k = orientation.to_keyboard(safe_area);
+                assert_eq!(orientation.to_screen(k), safe_area);
+
+                let keyboard_rect = Rect::new(k[0], long - k[2], k[1], short - k[3]);
+                let screen_rect = orientation
+                    .transform(width, height)
+                    .apply_rect(&keyboard_rect);
+                assert_eq!(
+                    screen_rect,
+                    Rect::new(
+                        safe_area[0],
+                        width - safe_area[2],
+                        safe_area[1],
+                        height - safe_area[3]
+                    ),
+                    "{orientation:?}"
+                );
+                assert_eq!(
+                    orientation.rect_to_keyboard(&screen_rect, width, height),
+                    keyboard_rect
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,509 @@
+use thiserror::Error;
+
+use super::TuningSystem;
+
+/// Problems found while reading Scala scale and keyboard mapping files.
+///
+/// Line numbers count from 1 and include comment lines.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ScalaError {
+    #[error("missing description line")]
+    MissingDescription,
+    #[error("missing note count")]
+    MissingCount,
+    #[error("line {line}: invalid note count `{text}`")]
+    InvalidCount { line: usize, text: String },
+    #[error("line {line}: invalid pitch `{text}`")]
+    InvalidPitch { line: usize, text: String },
+    #[error("line {line}: pitch `{text}` must be above zero")]
+    NonPositivePitch { line: usize, text: String },
+    #[error("expected {expected} pitches, found {found}")]
+    CountMismatch { expected: usize, found: usize },
+    #[error("scale has no pitches")]
+    EmptyScale,
+    #[error("missing keyboard mapping field: {0}")]
+    MissingField(&'static str),
+    #[error("line {line}: invalid {field} `{text}`")]
+    InvalidField {
+        line: usize,
+        field: &'static str,
+        text: String,
+    },
+    #[error("line {line}: more mapping entries than the map size {size}")]
+    TooManyEntries { line: usize, size: usize },
+    #[error("mapped degree {degree} is outside the scale of {size} pitches")]
+    DegreeOutOfRange { degree: usize, size: usize },
+    #[error("keyboard mapping has no mapped keys")]
+    NoMappedKeys,
+    #[error("reference note {0} is not mapped")]
+    UnmappedReference(u8),
+    #[error("notes {first}..={last} do not cover the {size} keys from the middle note {middle}")]
+    RangeTooNarrow {
+        first: u8,
+        last: u8,
+        middle: u8,
+        size: usize,
+    },
+}
+
+/// A pitch line of a `.scl` file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pitch {
+    Cents(f64),
+    Ratio(u64, u64),
+}
+
+impl Pitch {
+    pub fn ratio(&self) -> f64 {
+        match *self {
+            Self::Cents(cents) => 2_f64.powf(cents / 1200.0),
+            Self::Ratio(num, den) => num as f64 / den as f64,
+        }
+    }
+}
+
+/// Contents of a `.scl` file, `1/1` implied, the last pitch is the period.
+///
+/// Only built by [`Scale::parse`], which rejects empty scales.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scale {
+    description: String,
+    pitches: Vec<Pitch>,
+}
+
+impl Scale {
+    pub fn parse(text: &str) -> Result<Self, ScalaError> {
+        let mut lines = significant_lines(text);
+
+        let description = lines
+            .next()
+            .map(|(_, line)| line.trim().to_string())
+            .ok_or(ScalaError::MissingDescription)?;
+
+        let expected = {
+            let (line, text) = lines
+                .by_ref()
+                .find(|(_, line)| !line.trim().is_empty())
+                .ok_or(ScalaError::MissingCount)?;
+            let token = first_token(text);
+            token.parse::<usize>().map_err(|_| ScalaError::InvalidCount {
+                line,
+                text: token.to_string(),
+            })?
+        };
+
+        let pitches = lines
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(line, text)| parse_pitch(line, first_token(text)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if pitches.len() != expected {
+            return Err(ScalaError::CountMismatch {
+                expected,
+                found: pitches.len(),
+            });
+        }
+        if pitches.is_empty() {
+            return Err(ScalaError::EmptyScale);
+        }
+
+        Ok(Self {
+            description,
+            pitches,
+        })
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn pitches(&self) -> &[Pitch] {
+        &self.pitches
+    }
+
+    pub fn len(&self) -> usize {
+        self.pitches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pitches.is_empty()
+    }
+
+    /// Ratio of `degree` to the root, degrees past the period repeat it.
+    pub fn ratio(&self, degree: i64) -> f64 {
+        let size = self.len() as i64;
+        let period = self.pitches.last().map_or(2.0, Pitch::ratio);
+        let step = degree.rem_euclid(size) as usize;
+        let ratio = if step == 0 {
+            1.0
+        } else {
+            self.pitches[step - 1].ratio()
+        };
+
+        period.powi(degree.div_euclid(size) as i32) * ratio
+    }
+
+    /// Tuning system placing the keys of `mapping` onto the buttons,
+    /// `f_n` 1 on the first mapped key from the middle note upwards and
+    /// one mapped key per button, repeating the map period. The notes
+    /// from `first_note` to `last_note` must cover that period. Without
+    /// a mapping every degree gets a button, with the root at `f0`.
+    pub fn tuning_system(
+        &self,
+        mapping: Option<&KeyboardMapping>,
+    ) -> Result<TuningSystem, ScalaError> {
+        let Some(mapping) = mapping else {
+            return Ok(TuningSystem::Scala {
+                root_hz: None,
+                ratios: self.pitches.iter().map(Pitch::ratio).collect(),
+            });
+        };
+
+        if let Some(degree) = mapping
+            .keys
+            .iter()
+            .flatten()
+            .find(|degree| **degree >= self.len())
+        {
+            return Err(ScalaError::DegreeOutOfRange {
+                degree: *degree,
+                size: self.len(),
+            });
+        }
+
+        let size = if mapping.keys.is_empty() {
+            self.len()
+        } else {
+            mapping.keys.len()
+        };
+        if mapping.middle_note < mapping.first_note
+            || mapping.middle_note as usize + size - 1 > mapping.last_note as usize
+        {
+            return Err(ScalaError::RangeTooNarrow {
+                first: mapping.first_note,
+                last: mapping.last_note,
+                middle: mapping.middle_note,
+                size,
+            });
+        }
+
+        let reference = mapping
+            .degree(mapping.reference_note)
+            .ok_or(ScalaError::UnmappedReference(mapping.reference_note))?;
+        let at_degree =
+            |degree: i64| mapping.reference_freq * self.ratio(degree) / self.ratio(reference);
+
+        if mapping.keys.is_empty() {
+            return Ok(TuningSystem::Scala {
+                root_hz: Some(at_degree(0) as f32),
+                ratios: self.pitches.iter().map(Pitch::ratio).collect(),
+            });
+        }
+
+        let mapped = mapping
+            .keys
+            .iter()
+            .flatten()
+            .map(|degree| *degree as i64)
+            .collect::<Vec<_>>();
+        let first = *mapped.first().ok_or(ScalaError::NoMappedKeys)?;
+        let period = first + mapping.octave_degree as i64;
+
+        Ok(TuningSystem::Scala {
+            root_hz: Some(at_degree(first) as f32),
+            ratios: mapped
+                .iter()
+                .skip(1)
+                .chain(std::iter::once(&period))
+                .map(|degree| self.ratio(*degree) / self.ratio(first))
+                .collect(),
+        })
+    }
+}
+
+/// Contents of a `.kbm` file.
+///
+/// `keys` holds the scale degree of each key in a map period,
+/// `None` for unmapped keys; empty means a linear mapping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardMapping {
+    pub first_note: u8,
+    pub last_note: u8,
+    pub middle_note: u8,
+    pub reference_note: u8,
+    pub reference_freq: f64,
+    pub octave_degree: usize,
+    pub keys: Vec<Option<usize>>,
+}
+
+impl KeyboardMapping {
+    pub fn parse(text: &str) -> Result<Self, ScalaError> {
+        let mut lines = significant_lines(text).filter(|(_, line)| !line.trim().is_empty());
+
+        let mut next_field = |field: &'static str| {
+            lines
+                .next()
+                .map(|(line, text)| (line, first_token(text).to_string()))
+                .ok_or(ScalaError::MissingField(field))
+        };
+
+        let size = parse_field::<usize>(next_field("map size")?, "map size")?;
+        let first_note = parse_field::<u8>(next_field("first note")?, "first note")?;
+        let last_note = parse_field::<u8>(next_field("last note")?, "last note")?;
+        let middle_note = parse_field::<u8>(next_field("middle note")?, "middle note")?;
+        let reference_note = parse_field::<u8>(next_field("reference note")?, "reference note")?;
+        let reference_freq = {
+            let (line, text) = next_field("reference frequency")?;
+            match text.parse::<f64>() {
+                Ok(freq) if freq.is_finite() && freq > 0.0 => freq,
+                _ => {
+                    return Err(ScalaError::InvalidField {
+                        line,
+                        field: "reference frequency",
+                        text,
+                    })
+                }
+            }
+        };
+        let (octave_line, octave_text) = next_field("octave degree")?;
+        let octave_degree = parse_field::<usize>((octave_line, octave_text), "octave degree")?;
+        if size > 0 && octave_degree == 0 {
+            return Err(ScalaError::InvalidField {
+                line: octave_line,
+                field: "octave degree",
+                text: octave_degree.to_string(),
+            });
+        }
+
+        let mut keys = lines
+            .map(|(line, text)| match first_token(text) {
+                "x" | "X" => Ok((line, None)),
+                token => token
+                    .parse::<usize>()
+                    .map(|degree| (line, Some(degree)))
+                    .map_err(|_| ScalaError::InvalidField {
+                        line,
+                        field: "mapping entry",
+                        text: token.to_string(),
+                    }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some((line, _)) = keys.get(size) {
+            return Err(ScalaError::TooManyEntries { line: *line, size });
+        }
+        keys.resize(size, (0, None));
+
+        Ok(Self {
+            first_note,
+            last_note,
+            middle_note,
+            reference_note,
+            reference_freq,
+            octave_degree,
+            keys: keys.into_iter().map(|(_, degree)| degree).collect(),
+        })
+    }
+
+    /// Scale degree of MIDI `note`, relative to the middle note.
+    pub fn degree(&self, note: u8) -> Option<i64> {
+        if note < self.first_note || note > self.last_note {
+            return None;
+        }
+
+        let offset = note as i64 - self.middle_note as i64;
+        if self.keys.is_empty() {
+            return Some(offset);
+        }
+
+        let size = self.keys.len() as i64;
+        self.keys[offset.rem_euclid(size) as usize].map(|degree| {
+            offset.div_euclid(size) * self.octave_degree as i64 + degree as i64
+        })
+    }
+}
+
+fn significant_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.starts_with('!'))
+}
+
+fn first_token(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or_default()
+}
+
+fn parse_field<T: std::str::FromStr>(
+    (line, text): (usize, String),
+    field: &'static str,
+) -> Result<T, ScalaError> {
+    text.parse::<T>()
+        .map_err(|_| ScalaError::InvalidField { line, field, text })
+}
+
+fn parse_pitch(line: usize, text: &str) -> Result<Pitch, ScalaError> {
+    let invalid = || ScalaError::InvalidPitch {
+        line,
+        text: text.to_string(),
+    };
+    let non_positive = || ScalaError::NonPositivePitch {
+        line,
+        text: text.to_string(),
+    };
+
+    if text.contains('.') {
+        let cents = text.parse::<f64>().map_err(|_| invalid())?;
+        return if cents.is_finite() {
+            Ok(Pitch::Cents(cents))
+        } else {
+            Err(invalid())
+        };
+    }
+
+    if text.starts_with('-') {
+        return Err(non_positive());
+    }
+
+    let (num, den) = match text.split_once('/') {
+        Some((num, den)) => (num, den),
+        None => (text, "1"),
+    };
+    let num = num.parse::<u64>().map_err(|_| invalid())?;
+    let den = den.parse::<u64>().map_err(|_| invalid())?;
+
+    if num == 0 || den == 0 {
+        Err(non_positive())
+    } else {
+        Ok(Pitch::Ratio(num, den))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_scale_errors_with_lines() {
+        assert_eq!(Scale::parse("! only comments\n"), Err(ScalaError::MissingDescription));
+        assert_eq!(Scale::parse("desc\n"), Err(ScalaError::MissingCount));
+        assert_eq!(
+            Scale::parse("desc\n twelve\n"),
+            Err(ScalaError::InvalidCount {
+                line: 2,
+                text: "twelve".to_string()
+            })
+        );
+        assert_eq!(
+            Scale::parse("desc\n 2\n!\n 3/2\n 2/x\n"),
+            Err(ScalaError::InvalidPitch {
+                line: 5,
+                text: "2/x".to_string()
+            })
+        );
+        assert_eq!(
+            Scale::parse("desc\n 2\n 3/0\n 2/1\n"),
+            Err(ScalaError::NonPositivePitch {
+                line: 3,
+                text: "3/0".to_string()
+            })
+        );
+        assert_eq!(
+            Scale::parse("desc\n 3\n 3/2\n 2/1\n"),
+            Err(ScalaError::CountMismatch {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(Scale::parse("desc\n 0\n"), Err(ScalaError::EmptyScale));
+    }
+
+    #[test]
+    fn parses_pitch_forms() {
+        let scale = Scale::parse("\n 4\n 100.0 cents\n 5/4 major third\n 3\n 1200.\n").unwrap();
+
+        assert_eq!(scale.description, "");
+        assert_eq!(
+            scale.pitches,
+            vec![
+                Pitch::Cents(100.0),
+                Pitch::Ratio(5, 4),
+                Pitch::Ratio(3, 1),
+                Pitch::Cents(1200.0)
+            ]
+        );
+        assert_eq!(scale.ratio(4), 2.0);
+        assert_eq!(scale.ratio(-2), 0.625);
+    }
+
+    #[test]
+    fn reports_mapping_errors_with_lines() {
+        assert_eq!(
+            KeyboardMapping::parse("12\n0\n127\n60\n"),
+            Err(ScalaError::MissingField("reference note"))
+        );
+        assert_eq!(
+            KeyboardMapping::parse("12\n0\n127\n60\n69\n-440\n12\n"),
+            Err(ScalaError::InvalidField {
+                line: 6,
+                field: "reference frequency",
+                text: "-440".to_string()
+            })
+        );
+        assert_eq!(
+            KeyboardMapping::parse("2\n0\n127\n60\n69\n440\n12\n0\ny\n"),
+            Err(ScalaError::InvalidField {
+                line: 9,
+                field: "mapping entry",
+                text: "y".to_string()
+            })
+        );
+        assert_eq!(
+            KeyboardMapping::parse("1\n0\n127\n60\n60\n440\n1\n0\n0\n"),
+            Err(ScalaError::TooManyEntries { line: 9, size: 1 })
+        );
+    }
+
+    #[test]
+    fn maps_keys_to_degrees() {
+        let mapping = KeyboardMapping::parse("3\n0\n127\n60\n60\n261.6\n5\n0\nx\n2\n").unwrap();
+
+        assert_eq!(mapping.keys, vec![Some(0), None, Some(2)]);
+        assert_eq!(mapping.degree(60), Some(0));
+        assert_eq!(mapping.degree(61), None);
+        assert_eq!(mapping.degree(62), Some(2));
+        assert_eq!(mapping.degree(63), Some(5));
+        assert_eq!(mapping.degree(59), Some(-3));
+
+        let scale = Scale::parse("desc\n 1\n 2/1\n").unwrap();
+        assert_eq!(
+            scale.tuning_system(Some(&mapping)),
+            Err(ScalaError::DegreeOutOfRange { degree: 2, size: 1 })
+        );
+    }
+
+    #[test]
+    fn rejects_mappings_narrower_than_a_period() {
+        let scale = Scale::parse("desc\n 3\n 5/4\n 3/2\n 2/1\n").unwrap();
+
+        let mapping = KeyboardMapping::parse("3\n60\n61\n60\n60\n261.6\n3\n0\n1\n2\n").unwrap();
+        assert_eq!(
+            scale.tuning_system(Some(&mapping)),
+            Err(ScalaError::RangeTooNarrow {
+                first: 60,
+                last: 61,
+                middle: 60,
+                size: 3
+            })
+        );
+
+        let linear = KeyboardMapping::parse("0\n61\n127\n60\n69\n440\n0\n").unwrap();
+        assert!(matches!(
+            scale.tuning_system(Some(&linear)),
+            Err(ScalaError::RangeTooNarrow { first: 61, .. })
+        ));
+
+        let mapping = KeyboardMapping::parse("3\n60\n62\n60\n60\n261.6\n3\n0\n1\n2\n").unwrap();
+        assert!(scale.tuning_system(Some(&mapping)).is_ok());
+    }
+}
@@ -1,10 +1,16 @@
 use hecs::{Bundle, Entity, World};
 use mint::Point2;
 
+use crate::animate::{follow, SETTLED};
 use crate::geometry::Line;
 
 use super::Config;
 
+/// Smallest peak the string is normalized by, keeps the noise floor flat.
+const MIN_PEAK: f32 = 1.0e-3;
+/// Peak decay per update, so the string doesn't jump between frames.
+const PEAK_DECAY: f32 = 0.95;
+
 #[derive(Default, Bundle)]
 pub struct InboundString {
     pub line: Line,
@@ -13,7 +19,7 @@ pub struct InboundString {
 impl InboundString {
     pub fn spawn(world: &mut World, config: &Config) -> Entity {
         world.spawn((InboundString {
-            line: string_line(config, 2.0),
+            line: string_line(config, 2.0, 0),
         },))
     }
 }
@@ -21,60 +27,129 @@ impl InboundString {
 #[derive(Default, Bundle)]
 pub struct OutboundString {
     pub line: Line,
+    /// Points as shown, following `target`.
     pub data: Vec<Point2<f64>>,
+    pub target: Vec<Point2<f64>>,
+    pub peak: f32,
 }
 
 impl OutboundString {
     pub fn spawn(world: &mut World, config: &Config) -> Entity {
         world.spawn((OutboundString {
-            line: string_line(config, 1.0),
+            line: string_line(config, 1.0, config.rows),
             data: vec![],
+            target: vec![],
+            peak: MIN_PEAK,
         },))
     }
 
     pub fn update_data(&mut self, data: Vec<f32>, config: &Config) {
         let l_step = self.line.len() / data.len() as f64;
         let b_step = config.breadth / 2.25;
-        let b_base = if config.portrait {
-            self.line.p0().x
-        } else {
-            self.line.p0().y
-        };
+        let to_screen = config.transform();
+        let b_base = to_screen
+            .inverse()
+            .unwrap_or_default()
+            .apply(self.line.p0())
+            .y;
         log::debug!("l_step {l_step}; total: {}; width: {}", l_step * data.len() as f64, config.width);
-        
-        self.data = data
-        .into_iter()
-        .enumerate()
-        .map(|(i, val)| {
-                let val = val / 128.0;
+
+        let peak = data.iter().fold(0.0_f32, |peak, val| peak.max(val.abs()));
+        self.peak = peak.max(self.peak * PEAK_DECAY).max(MIN_PEAK);
+
+        self.target = data
+            .into_iter()
+            .enumerate()
+            .map(|(i, val)| {
+                let val = val / self.peak;
                 let l = i as f64 * l_step;
                 let b = b_base + b_step * val as f64;
-                if config.portrait {
-                    Point2 { x: b, y: l }
-                } else {
-                    Point2 { x: l, y: b }
-                }
+                to_screen.apply(Point2 { x: l, y: b })
             })
             .collect();
     }
+
+    /// Moves the shown points towards the target, `false` once they're there.
+    pub fn follow(&mut self, dt: f64) -> bool {
+        if self.data.len() != self.target.len() {
+            self.data = self.target.clone();
+            return false;
+        }
+
+        let mut moving = false;
+        for (pt, to) in self.data.iter_mut().zip(self.target.iter()) {
+            *pt = follow(*pt, *to, dt);
+            moving |= (pt.x - to.x).hypot(pt.y - to.y) > SETTLED;
+        }
+        moving
+    }
 }
 
-fn string_line(config: &Config, at: f64) -> Line {
-    if config.portrait {
-        let main = (config.width - config.breadth) / at;
-        Line::new(
-            main,
-            main,
-            0.0,
-            config.length + config.safe_area[3] + config.safe_area[1] + config.whitespace * 2.0,
-        )
+/// Strings run along the edges of the keyboard, `row` picks the edge when
+/// it isn't a single strip.
+fn string_line(config: &Config, at: f64, row: usize) -> Line {
+    let (long, short) = config.extent();
+    let (side, length) = if !config.is_single_strip() {
+        (config.row_start(row), long)
     } else {
-        let main = (config.height - config.breadth) / at;
-        Line::new(
-            0.0,
-            config.length + config.safe_area[2] + config.safe_area[0] + config.whitespace * 2.0,
-            main,
-            main,
+        let area = config.keyboard_area();
+        (
+            (short - config.breadth) / at,
+            config.length + area[2] + area[0] + config.whitespace * 2.0,
         )
+    };
+
+    config
+        .transform()
+        .apply_line(&Line::new(0.0, length, side, side))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_by_peak() {
+        let config = Config::new(1920.0, 1080.0, 96.0, Default::default());
+        let mut world = World::new();
+        let entity = OutboundString::spawn(&mut world, &config);
+        let mut string = world.get::<&mut OutboundString>(entity).unwrap();
+        let b_base = string.line.p0().y;
+        let b_step = config.breadth / 2.25;
+
+        string.update_data(vec![0.0, 0.02, -0.04, 0.01], &config);
+        assert_eq!(string.peak, 0.04);
+        let offsets = string
+            .target
+            .iter()
+            .map(|p| p.y - b_base)
+            .collect::<Vec<_>>();
+        assert!((offsets[2] + b_step).abs() < 1.0e-6);
+        assert!(offsets.iter().all(|b| b.abs() <= b_step + 1.0e-6));
+
+        string.update_data(vec![0.0; 4], &config);
+        assert_eq!(string.peak, 0.04 * PEAK_DECAY);
+    }
+
+    #[test]
+    fn follows_the_output() {
+        let config = Config::new(1920.0, 1080.0, 96.0, Default::default());
+        let mut world = World::new();
+        let entity = OutboundString::spawn(&mut world, &config);
+        let mut string = world.get::<&mut OutboundString>(entity).unwrap();
+        let apart = |a: Point2<f64>, b: Point2<f64>| (a.x - b.x).hypot(a.y - b.y);
+
+        string.update_data(vec![0.0, 0.5, -0.5, 0.0], &config);
+        assert!(!string.follow(16.0));
+        assert_eq!(string.data, string.target);
+
+        let from = string.data.clone();
+        string.update_data(vec![0.0, -0.5, 0.5, 0.0], &config);
+        assert!(string.follow(16.0));
+        assert!(apart(string.data[1], from[1]) > 0.0);
+        assert!(apart(string.data[1], string.target[1]) > SETTLED);
+
+        while string.follow(16.0) {}
+        assert!(apart(string.data[1], string.target[1]) <= SETTLED);
     }
 }
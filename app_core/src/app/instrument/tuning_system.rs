@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// 5-limit just major scale, the last ratio is the period.
+const JUST_MAJOR: &[f64] = &[
+    9.0 / 8.0,
+    5.0 / 4.0,
+    4.0 / 3.0,
+    3.0 / 2.0,
+    5.0 / 3.0,
+    15.0 / 8.0,
+    2.0,
+];
+
+/// How node frequencies are derived from the root.
+///
+/// Roots left as `None` follow `Config.f0`.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub enum TuningSystem {
+    /// Odd harmonics of the root, the original red siren tuning.
+    #[default]
+    Harmonic,
+    /// `divisions` equal steps per octave.
+    EqualTemperament { root_hz: Option<f32>, divisions: u32 },
+    /// Just major scale repeated every octave.
+    JustIntonation { root_hz: Option<f32> },
+    /// Ratios of a Scala scale, `1/1` implied, the last ratio is the period.
+    Scala { root_hz: Option<f32>, ratios: Vec<f64> },
+}
+
+impl Eq for TuningSystem {}
+
+impl TuningSystem {
+    /// Frequency of the `degree`-th node above the root, counting from 0.
+    pub fn freq(&self, f0: f32, degree: usize) -> f32 {
+        match self {
+            Self::Harmonic => f0 * (degree * 2 + 1) as f32,
+            Self::EqualTemperament { root_hz, divisions } => {
+                let root = root_hz.unwrap_or(f0) as f64;
+                (root * 2_f64.powf(degree as f64 / (*divisions).max(1) as f64)) as f32
+            }
+            Self::JustIntonation { root_hz } => {
+                ratio_freq(root_hz.unwrap_or(f0), JUST_MAJOR, degree)
+            }
+            Self::Scala { root_hz, ratios } => ratio_freq(root_hz.unwrap_or(f0), ratios, degree),
+        }
+    }
+
+    /// Frequency band of the node `f_n`, from its pitch up to
+    /// halfway to the next node.
+    pub fn band(&self, f0: f32, f_n: usize) -> (f32, f32) {
+        let degree = f_n.saturating_sub(1);
+        let freq = self.freq(f0, degree);
+        let next = self.freq(f0, degree + 1);
+
+        (freq, (freq + next) / 2.0)
+    }
+}
+
+fn ratio_freq(root: f32, ratios: &[f64], degree: usize) -> f32 {
+    let ratios = ratios
+        .iter()
+        .copied()
+        .filter(|r| r.is_finite() && *r > 0.0)
+        .collect::<Vec<_>>();
+
+    match ratios.last() {
+        Some(period) if *period > 1.0 => {
+            let periods = (degree / ratios.len()) as i32;
+            let step = degree % ratios.len();
+            let ratio = if step == 0 { 1.0 } else { ratios[step - 1] };
+
+            (root as f64 * period.powi(periods) * ratio) as f32
+        }
+        _ => {
+            log::warn!("scale without a period, using harmonics");
+            TuningSystem::Harmonic.freq(root, degree)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn harmonic_matches_odd_harmonics() {
+        let f0 = 110.0;
+        for f_n in 1..=12 {
+            let band = TuningSystem::Harmonic.band(f0, f_n);
+            assert_eq!(band, (f0 * (f_n * 2) as f32 - f0, f0 * (f_n * 2) as f32));
+        }
+    }
+
+    #[test]
+    fn equal_temperament_doubles_every_octave() {
+        let system = TuningSystem::EqualTemperament {
+            root_hz: Some(220.0),
+            divisions: 12,
+        };
+        assert_eq!(system.freq(110.0, 0), 220.0);
+        assert_eq!(system.freq(110.0, 12), 440.0);
+        assert!((system.freq(110.0, 7) - 329.6276).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn ratio_scales_repeat_at_the_period() {
+        let just = TuningSystem::JustIntonation { root_hz: None };
+        assert_eq!(just.freq(100.0, 0), 100.0);
+        assert_eq!(just.freq(100.0, 2), 125.0);
+        assert_eq!(just.freq(100.0, 7), 200.0);
+        assert_eq!(just.freq(100.0, 11), 300.0);
+
+        let tritave = TuningSystem::Scala {
+            root_hz: Some(100.0),
+            ratios: vec![5.0 / 3.0, 3.0],
+        };
+        assert_eq!(tritave.freq(1.0, 3), 500.0);
+        assert_eq!(tritave.freq(1.0, 4), 900.0);
+
+        let broken = TuningSystem::Scala {
+            root_hz: None,
+            ratios: vec![],
+        };
+        assert_eq!(broken.freq(100.0, 1), 300.0);
+    }
+}
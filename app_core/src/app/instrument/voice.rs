@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// Timbre of a chime node, rendered by the audio core.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Voice {
+    /// Sine partial through a bell filter, the original red siren sound.
+    #[default]
+    SineBell,
+    /// Karplus-Strong string excited by the incoming band.
+    PluckedString,
+    /// Two operator FM, modulator at `ratio` of the node frequency.
+    Fm { ratio: f32, index: f32 },
+    /// Noise filtered around the node frequency.
+    NoiseBand { q: f32 },
+    /// User provided sample from `slot`, recorded at `root_hz`,
+    /// triggered when the node input rises above `threshold`.
+    Sample {
+        slot: usize,
+        root_hz: f32,
+        threshold: f32,
+    },
+}
+
+impl Eq for Voice {}
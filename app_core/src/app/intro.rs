@@ -6,11 +6,13 @@ use mint::{Point2, Point3};
 use serde::{Deserialize, Serialize};
 
 use animation::IntroAnimation;
+pub use timeline::{Timeline, TimelineError};
 
-use crate::animate::Animate;
+use crate::animate::{Animate, Clock, ClockEV, INTRO_ANIMATION};
 use crate::{geometry::Rect, instrument, Activity, Navigate};
 
 mod animation;
+pub mod timeline;
 
 #[derive(Default)]
 pub struct Intro;
@@ -20,9 +22,12 @@ pub struct Model {
     pub layout: instrument::Layout,
     pub config: instrument::Config,
     pub sequence: Option<IntroAnimation>,
+    pub clock: Clock,
     pub reduced_motion: bool,
     pub transition_to: Option<Activity>,
     pub current_activity: Activity,
+    pub timeline: Timeline,
+    pub timeline_error: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, CanTween)]
@@ -72,7 +77,10 @@ impl Default for IntroVM {
 pub enum IntroEV {
     SetInstrumentTarget(Box<instrument::Layout>, Box<instrument::Config>),
     SetReducedMotion(bool),
+    /// Replaces the timeline with JSON from the shell, keeps it when invalid.
+    LoadTimeline(String),
     TsNext(f64),
+    Clock(ClockEV),
     Menu(Activity),
     Start,
 }
@@ -100,6 +108,7 @@ impl App for Intro {
     fn update(&self, event: Self::Event, model: &mut Self::Model, caps: &Self::Capabilities) {
         match event {
             IntroEV::Start => caps.animate.start(
+                INTRO_ANIMATION,
                 IntroEV::TsNext,
                 model
                     .sequence
@@ -122,7 +131,21 @@ impl App for Intro {
                 model.reduced_motion = reduced_motion;
                 caps.render.render();
             }
+            IntroEV::LoadTimeline(json) => {
+                match Timeline::parse(json.as_str()) {
+                    Ok(timeline) => {
+                        model.timeline = timeline;
+                        model.timeline_error = None;
+                    }
+                    Err(e) => {
+                        log::error!("timeline not loaded: {e}");
+                        model.timeline_error = Some(e.to_string());
+                    }
+                }
+                caps.render.render();
+            }
             IntroEV::TsNext(ts) => {
+                let ts = model.clock.tick(ts);
                 if let Some(seq) = model.sequence.as_mut() {
                     let ended = seq.tick(ts, model.reduced_motion);
                     if ended {
@@ -130,7 +153,7 @@ impl App for Intro {
                             caps.navigate.to(to);
                             log::info!("navigating");
                         }
-                        caps.animate.stop();
+                        caps.animate.stop(INTRO_ANIMATION);
                         log::info!("{:?} animation ended", seq.animation);
                     }
                     caps.render.render();
@@ -138,23 +161,48 @@ impl App for Intro {
                     panic!("animation started without sequence");
                 }
             }
-            IntroEV::Menu(next_activity) => {
-                model.sequence = Some(IntroAnimation::new(model, next_activity));
-                caps.animate.stop();
-                match next_activity {
-                    Activity::Intro => {
-                        caps.navigate.to(Activity::Intro);
+            IntroEV::Clock(event) => model.clock.update(event),
+            IntroEV::Menu(next_activity) => match model.sequence.take() {
+                Some(mut current) if current.is_running() => {
+                    if current.heading() == next_activity {
+                        log::debug!("already heading to {next_activity:?}");
+                    } else if current.leaving() == next_activity {
+                        current.reverse();
+                    } else {
+                        current = current.redirect(model, next_activity);
                     }
-                    _ => {
-                        _ = model.transition_to.insert(next_activity);
-                        caps.animate.start(
-                            IntroEV::TsNext,
-                            model.sequence.as_ref().map(|s| format!("{:?}", s.animation)).unwrap(),
-                        );
+                    model.transition_to =
+                        Some(current.heading()).filter(|to| *to != model.current_activity);
+                    model.sequence = Some(current);
+                    log::info!("transition interrupted for {next_activity:?}");
+                }
+                current if next_activity == model.current_activity => {
+                    model.sequence = current;
+                    log::debug!("already at {next_activity:?}");
+                }
+                _ => {
+                    model.sequence = Some(IntroAnimation::new(model, next_activity));
+                    caps.animate.stop(INTRO_ANIMATION);
+                    match next_activity {
+                        Activity::Intro => {
+                            caps.navigate.to(Activity::Intro);
+                        }
+                        _ => {
+                            _ = model.transition_to.insert(next_activity);
+                            caps.animate.start(
+                                INTRO_ANIMATION,
+                                IntroEV::TsNext,
+                                model
+                                    .sequence
+                                    .as_ref()
+                                    .map(|s| format!("{:?}", s.animation))
+                                    .unwrap(),
+                            );
+                        }
                     }
+                    log::info!("scheduled transition to {next_activity:?}");
                 }
-                log::info!("scheduled transition to {next_activity:?}");
-            }
+            },
         }
     }
 
@@ -173,3 +221,125 @@ impl App for Intro {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crux_core::testing::AppTester;
+
+    use super::*;
+
+    fn send(app: &AppTester<Intro, Effect>, model: &mut Model, event: IntroEV) {
+        _ = app.update(event, model);
+    }
+
+    /// Frames every 12.5ms after `from` up to `to`.
+    fn frames(app: &AppTester<Intro, Effect>, model: &mut Model, from: f64, to: f64) {
+        let count = ((to - from) / 12.5).round() as usize;
+        for i in 1..=count {
+            send(app, model, IntroEV::TsNext(from + i as f64 * 12.5));
+        }
+    }
+
+    fn halfway_to_play() -> (AppTester<Intro, Effect>, Model) {
+        let app = AppTester::<Intro, Effect>::default();
+        let mut model = Model::default();
+
+        send(
+            &app,
+            &mut model,
+            IntroEV::SetInstrumentTarget(Box::default(), Box::default()),
+        );
+        send(&app, &mut model, IntroEV::Menu(Activity::Play));
+        send(&app, &mut model, IntroEV::TsNext(0.0));
+        frames(&app, &mut model, 0.0, 375.0);
+
+        (app, model)
+    }
+
+    #[test]
+    fn reverses_an_interrupted_transition() {
+        let (app, mut model) = halfway_to_play();
+        let halfway = app.view(&model).menu_flip;
+        assert!(halfway > 0.0 && halfway < 180.0);
+
+        send(&app, &mut model, IntroEV::Menu(Activity::Intro));
+        assert_eq!(model.transition_to, None);
+        assert_eq!(app.view(&model).menu_flip, halfway);
+
+        frames(&app, &mut model, 375.0, 562.5);
+        let back = app.view(&model).menu_flip;
+        assert!(back > 0.0 && back < halfway);
+
+        frames(&app, &mut model, 562.5, 750.0);
+        assert_eq!(app.view(&model).menu_flip, 0.0);
+        assert!(!model.sequence.as_ref().unwrap().is_running());
+        assert_eq!(model.transition_to, None);
+
+        // playback started for the transition stops with it
+        let app = AppTester::<crate::RedSiren, crate::Effect>::default();
+        let mut model = crate::Model::default();
+        {
+            let mut world = model.tuner.world.lock().expect("world lock");
+            model.tuner.chart = Some(crate::tuner::Chart::new(&mut world, &model.tuner.config));
+        }
+
+        _ = app.update(
+            crate::Event::IntroEvent(IntroEV::SetInstrumentTarget(Box::default(), Box::default())),
+            &mut model,
+        );
+        _ = app.update(crate::Event::Menu(Activity::Play), &mut model);
+        for i in 0..=30 {
+            _ = app.update(
+                crate::Event::IntroEvent(IntroEV::TsNext(i as f64 * 12.5)),
+                &mut model,
+            );
+        }
+        assert_eq!(model.intro.transition_to, Some(Activity::Play));
+        assert!(model.instrument.playing);
+
+        _ = app.update(crate::Event::Menu(Activity::Intro), &mut model);
+        assert_eq!(model.intro.transition_to, None);
+        assert!(!model.instrument.playing);
+    }
+
+    #[test]
+    fn redirects_from_what_is_displayed() {
+        let (app, mut model) = halfway_to_play();
+        let displayed = app.view(&model);
+
+        send(&app, &mut model, IntroEV::Menu(Activity::Tune));
+        assert_eq!(model.transition_to, Some(Activity::Tune));
+
+        let redirected = app.view(&model);
+        assert_eq!(redirected.menu_flip, displayed.menu_flip);
+        assert_eq!(
+            redirected.layout.menu_position.rect(),
+            displayed.layout.menu_position.rect()
+        );
+
+        send(&app, &mut model, IntroEV::Menu(Activity::Tune));
+        assert_eq!(model.transition_to, Some(Activity::Tune));
+
+        frames(&app, &mut model, 375.0, 1125.0);
+        assert!((app.view(&model).menu_flip - 180.0).abs() < 1e-9);
+        assert_eq!(model.transition_to, None);
+    }
+
+    #[test]
+    fn holds_a_backgrounded_transition() {
+        let (app, mut model) = halfway_to_play();
+        let halfway = app.view(&model).menu_flip;
+
+        send(&app, &mut model, IntroEV::Clock(ClockEV::Pause));
+        send(&app, &mut model, IntroEV::TsNext(400.0));
+        assert_eq!(app.view(&model).menu_flip, halfway);
+
+        send(&app, &mut model, IntroEV::Clock(ClockEV::Resume));
+        send(&app, &mut model, IntroEV::TsNext(60_000.0));
+        assert_eq!(app.view(&model).menu_flip, halfway);
+        assert_eq!(model.transition_to, Some(Activity::Play));
+
+        send(&app, &mut model, IntroEV::TsNext(60_012.5));
+        assert!(app.view(&model).menu_flip > halfway);
+    }
+}
@@ -0,0 +1,414 @@
+use keyframe::functions::{EaseIn, EaseInOut, EaseOut, Linear};
+use keyframe::{AnimationSequence, Keyframe};
+use mint::{Point2, Point3};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::geometry::{Line, Rect};
+use crate::instrument::{self, layout::MenuPosition};
+use crate::Activity;
+
+use super::{IntroVM, Model};
+
+/// Choreography shipped with the app, shells may load their own.
+const DEFAULT_TIMELINE: &str = include_str!("timeline.json");
+
+/// Transitions the app navigates through, each needs a matching entry.
+const REQUIRED: [(Activity, Activity); 8] = [
+    (Activity::Intro, Activity::Intro),
+    (Activity::Intro, Activity::About),
+    (Activity::About, Activity::Intro),
+    (Activity::Intro, Activity::Play),
+    (Activity::Play, Activity::Intro),
+    (Activity::Intro, Activity::Tune),
+    (Activity::Play, Activity::Tune),
+    (Activity::Tune, Activity::Intro),
+];
+
+/// Problems found while loading a timeline.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TimelineError {
+    #[error("invalid timeline: {0}")]
+    Parse(String),
+    #[error("transition {0} has no keyframes")]
+    NoKeyframes(String),
+    #[error("transition {0} must last longer than 0ms")]
+    InvalidDuration(String),
+    #[error("keyframes of {0} must run from 0 to 1 in order")]
+    KeyframeOrder(String),
+    #[error("no transition from {0:?} to {1:?}")]
+    Missing(Activity, Activity),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+/// State a keyframe starts from before its fields are applied.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+    /// The splash screen, before the instrument is known.
+    Initial,
+    /// The instrument laid out on the screen.
+    #[default]
+    Final,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MenuSpec {
+    /// Below the screen.
+    Offscreen,
+    /// Over the middle of the keyboard.
+    Center,
+    /// Collapsed to a button in the top left corner.
+    Corner,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TracksSpec {
+    Hidden,
+    /// Collapsed around their buttons.
+    AroundButtons,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum StringsSpec {
+    /// Both strings across the middle of the screen.
+    Center,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ButtonSize {
+    /// The button size of the instrument.
+    Target,
+    Fixed(f64),
+}
+
+/// Layout of a keyframe, starting from the final layout unless `base`
+/// says otherwise.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct LayoutSpec {
+    pub base: Base,
+    pub menu: Option<MenuSpec>,
+    pub tracks: Option<TracksSpec>,
+    pub strings: Option<StringsSpec>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KeyframeSpec {
+    /// Time from `0` to `1` of the transition.
+    pub at: f64,
+    /// Easing towards the next keyframe.
+    #[serde(default)]
+    pub ease: Ease,
+    #[serde(default)]
+    pub base: Base,
+    #[serde(default)]
+    pub layout: LayoutSpec,
+    pub button_size: Option<ButtonSize>,
+    pub intro_opacity: Option<f64>,
+    pub menu_opacity: Option<f64>,
+    pub menu_flip: Option<f64>,
+}
+
+/// Keyframes played when navigating `from` one activity `to` another,
+/// either left out matches any activity.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TransitionSpec {
+    pub name: String,
+    pub from: Option<Activity>,
+    pub to: Option<Activity>,
+    /// Length in ms.
+    pub duration: f64,
+    /// Plays the keyframes backwards.
+    #[serde(default)]
+    pub reverse: bool,
+    pub keyframes: Vec<KeyframeSpec>,
+}
+
+impl TransitionSpec {
+    /// Holds the final layout, for transitions the timeline leaves out.
+    pub fn still() -> Self {
+        let keyframe = |at| KeyframeSpec {
+            at,
+            ease: Ease::Linear,
+            base: Base::Final,
+            layout: LayoutSpec::default(),
+            button_size: None,
+            intro_opacity: None,
+            menu_opacity: None,
+            menu_flip: None,
+        };
+
+        Self {
+            name: "Still".to_string(),
+            from: None,
+            to: None,
+            duration: 1.0,
+            reverse: false,
+            keyframes: vec![keyframe(0.0), keyframe(1.0)],
+        }
+    }
+
+    pub fn matches(&self, from: Activity, to: Activity) -> bool {
+        self.from.map_or(true, |f| f == from) && self.to.map_or(true, |t| t == to)
+    }
+
+    pub fn sequence(&self, model: &Model) -> AnimationSequence<IntroVM> {
+        let frames = Frames::new(model);
+        let mut sequence = AnimationSequence::from(
+            self.keyframes
+                .iter()
+                .map(|kf| {
+                    let vm = frames.keyframe(kf);
+                    match kf.ease {
+                        Ease::Linear => Keyframe::new(vm, kf.at, Linear),
+                        Ease::EaseIn => Keyframe::new(vm, kf.at, EaseIn),
+                        Ease::EaseOut => Keyframe::new(vm, kf.at, EaseOut),
+                        Ease::EaseInOut => Keyframe::new(vm, kf.at, EaseInOut),
+                    }
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        if self.reverse {
+            sequence.reverse();
+        }
+
+        sequence
+    }
+
+    fn validate(&self) -> Result<(), TimelineError> {
+        if self.keyframes.is_empty() {
+            return Err(TimelineError::NoKeyframes(self.name.clone()));
+        }
+        if self.duration.is_nan() || self.duration <= 0.0 {
+            return Err(TimelineError::InvalidDuration(self.name.clone()));
+        }
+
+        let times = self.keyframes.iter().map(|kf| kf.at).collect::<Vec<_>>();
+        let in_order = times.windows(2).all(|w| w[0] < w[1]);
+        if !in_order || times.first() != Some(&0.0) || times.last() != Some(&1.0) {
+            return Err(TimelineError::KeyframeOrder(self.name.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Transitions of the intro, the first matching one plays.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Timeline {
+    pub transitions: Vec<TransitionSpec>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TIMELINE).expect("default timeline")
+    }
+}
+
+impl Timeline {
+    pub fn parse(json: &str) -> Result<Self, TimelineError> {
+        let timeline =
+            serde_json::from_str::<Self>(json).map_err(|e| TimelineError::Parse(e.to_string()))?;
+        timeline.validate()?;
+        Ok(timeline)
+    }
+
+    pub fn find(&self, from: Activity, to: Activity) -> Option<&TransitionSpec> {
+        self.transitions.iter().find(|t| t.matches(from, to))
+    }
+
+    fn validate(&self) -> Result<(), TimelineError> {
+        for transition in &self.transitions {
+            transition.validate()?;
+        }
+
+        match REQUIRED
+            .into_iter()
+            .find(|(from, to)| self.find(*from, *to).is_none())
+        {
+            Some((from, to)) => Err(TimelineError::Missing(from, to)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Named states keyframes are built from for the current instrument.
+struct Frames {
+    initial: IntroVM,
+    target: IntroVM,
+    button_size: f64,
+    center_menu: MenuPosition,
+    corner_menu: MenuPosition,
+    offscreen_menu: MenuPosition,
+    tracks_around_buttons: Vec<Rect>,
+    center_line: Line,
+}
+
+impl Frames {
+    fn new(model: &Model) -> Self {
+        let view_box = Rect::size(model.config.width, model.config.height);
+        let target = IntroVM {
+            intro_opacity: 0.0,
+            menu_opacity: 1.0,
+            view_box,
+            button_size: model.config.button_size,
+            flute_rotation: Point3::from([0.0, 0.0, 0.0]),
+            flute_position: Point2::from([0.0, 0.0]),
+            buttons_position: Point2::from([0.0, 0.0]),
+            layout: model.layout.clone(),
+            ..IntroVM::default()
+        };
+
+        let (long, short) = model.config.extent();
+        let menu_offset_main = model.config.length / 5.0 * -1.0;
+        let menu_offset_side = model.config.breadth / 5.0 * -1.0;
+        let center_rect = Rect::size(long, short)
+            .offset_left_and_right(menu_offset_main, menu_offset_main)
+            .offset_top_and_bottom(menu_offset_side, menu_offset_side);
+
+        let button_track_margin = model.config.button_size * model.config.button_track_margin;
+        let tracks_around_buttons = model
+            .layout
+            .buttons
+            .iter()
+            .map(|b| {
+                let (left, right, top, bottom) = b.components();
+                Rect::new(
+                    left - button_track_margin,
+                    right + button_track_margin,
+                    top - button_track_margin,
+                    bottom + button_track_margin,
+                )
+            })
+            .collect();
+
+        let center = view_box.center();
+
+        Self {
+            initial: IntroVM::default(),
+            button_size: model.config.button_size,
+            center_menu: MenuPosition::Center(model.config.transform().apply_rect(&center_rect)),
+            corner_menu: MenuPosition::TopLeft(
+                Rect::size(64.0, 64.0)
+                    .offset_left(model.config.safe_area[0])
+                    .offset_top(model.config.safe_area[1]),
+            ),
+            offscreen_menu: MenuPosition::Center(Rect::new(
+                0.0,
+                view_box.width(),
+                view_box.height(),
+                view_box.height() * 2.0,
+            )),
+            tracks_around_buttons,
+            center_line: Line::new(0.0, view_box.width(), center.y, center.y),
+            target,
+        }
+    }
+
+    fn base(&self, base: Base) -> &IntroVM {
+        match base {
+            Base::Initial => &self.initial,
+            Base::Final => &self.target,
+        }
+    }
+
+    fn layout(&self, spec: &LayoutSpec) -> instrument::Layout {
+        let mut layout = self.base(spec.base).layout.clone();
+
+        match spec.menu {
+            Some(MenuSpec::Offscreen) => layout.menu_position = self.offscreen_menu.clone(),
+            Some(MenuSpec::Center) => layout.menu_position = self.center_menu.clone(),
+            Some(MenuSpec::Corner) => layout.menu_position = self.corner_menu.clone(),
+            None => {}
+        }
+        match spec.tracks {
+            Some(TracksSpec::Hidden) => layout.tracks = vec![],
+            Some(TracksSpec::AroundButtons) => layout.tracks = self.tracks_around_buttons.clone(),
+            None => {}
+        }
+        if let Some(StringsSpec::Center) = spec.strings {
+            layout.inbound = self.center_line;
+            layout.outbound = self.center_line;
+        }
+
+        layout
+    }
+
+    fn keyframe(&self, spec: &KeyframeSpec) -> IntroVM {
+        let base = self.base(spec.base);
+
+        IntroVM {
+            layout: self.layout(&spec.layout),
+            button_size: match spec.button_size {
+                Some(ButtonSize::Target) => self.button_size,
+                Some(ButtonSize::Fixed(size)) => size,
+                None => base.button_size,
+            },
+            intro_opacity: spec.intro_opacity.unwrap_or(base.intro_opacity),
+            menu_opacity: spec.menu_opacity.unwrap_or(base.menu_opacity),
+            menu_flip: spec.menu_flip.unwrap_or(base.menu_flip),
+            ..base.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_default_timeline() {
+        let timeline = Timeline::default();
+        for (from, to) in REQUIRED {
+            assert!(timeline.find(from, to).is_some(), "{from:?} -> {to:?}");
+        }
+        assert_eq!(
+            timeline
+                .find(Activity::Tune, Activity::Play)
+                .map(|t| t.name.as_str()),
+            Some("PlayIntro")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_timelines() {
+        assert!(matches!(Timeline::parse("{"), Err(TimelineError::Parse(_))));
+        assert_eq!(
+            Timeline::parse(r#"{"transitions": []}"#),
+            Err(TimelineError::Missing(Activity::Intro, Activity::Intro))
+        );
+        assert_eq!(
+            Timeline::parse(
+                r#"{"transitions": [
+                    {"name": "All", "duration": 100, "keyframes": [{"at": 0.5}, {"at": 1}]}
+                ]}"#
+            ),
+            Err(TimelineError::KeyframeOrder("All".to_string()))
+        );
+        assert_eq!(
+            Timeline::parse(
+                r#"{"transitions": [
+                    {"name": "All", "duration": 0, "keyframes": [{"at": 0}, {"at": 1}]}
+                ]}"#
+            ),
+            Err(TimelineError::InvalidDuration("All".to_string()))
+        );
+
+        let timeline = Timeline::parse(
+            r#"{"transitions": [
+                {"name": "All", "duration": 100, "keyframes": [{"at": 0}, {"at": 1}]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(timeline.transitions[0].keyframes[0].ease, Ease::Linear);
+    }
+}
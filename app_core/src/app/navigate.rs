@@ -1,10 +1,13 @@
 use crux_core::capability::{CapabilityContext, Operation};
 use crux_macros::Capability;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::Activity;
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum NavigateOperation {
-    To(super::Activity),
+    To(Activity),
 }
 
 impl Operation for NavigateOperation {
@@ -24,7 +27,7 @@ where
         Self { context }
     }
 
-    pub fn to(&self, activity: super::Activity) {
+    pub fn to(&self, activity: Activity) {
         self.context.spawn({
             let context = self.context.clone();
             async move {
@@ -35,3 +38,320 @@ where
         });
     }
 }
+
+impl Activity {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Activity::Intro => "/",
+            Activity::Tune => "/tune",
+            Activity::Play => "/play",
+            Activity::Listen => "/listen",
+            Activity::About => "/about",
+        }
+    }
+
+    pub fn from_path(path: &str) -> Option<Self> {
+        match path.trim_end_matches('/') {
+            "" => Some(Activity::Intro),
+            "/tune" => Some(Activity::Tune),
+            "/play" => Some(Activity::Play),
+            "/listen" => Some(Activity::Listen),
+            "/about" => Some(Activity::About),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum RouteError {
+    #[error("no activity at {0}")]
+    UnknownPath(String),
+    #[error("malformed parameter {0}")]
+    Param(String),
+}
+
+/// An activity with parameters, as in a deep link: `/tune?preset=street`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Route {
+    pub activity: Activity,
+    pub params: Vec<(String, String)>,
+}
+
+impl Route {
+    pub fn new(activity: Activity) -> Self {
+        Self {
+            activity,
+            params: vec![],
+        }
+    }
+
+    /// Reads a path or a URL, `redsiren://tune` and `https://host/tune` alike.
+    pub fn parse(link: &str) -> Result<Self, RouteError> {
+        let link = link.split('#').next().unwrap_or_default();
+        let link = match link.split_once("://") {
+            Some((scheme, rest)) if scheme.starts_with("http") => {
+                rest.find('/').map_or("", |at| &rest[at..])
+            }
+            Some((_, rest)) => rest,
+            None => link,
+        };
+        let (path, query) = link.split_once('?').unwrap_or((link, ""));
+        let path = format!("/{}", path.trim_start_matches('/'));
+
+        let activity =
+            Activity::from_path(&path).ok_or_else(|| RouteError::UnknownPath(path.clone()))?;
+        let params = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                decode(key)
+                    .zip(decode(value))
+                    .ok_or_else(|| RouteError::Param(pair.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { activity, params })
+    }
+
+    pub fn path(&self) -> String {
+        let query = self
+            .params
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        if query.is_empty() {
+            self.activity.path().to_string()
+        } else {
+            format!("{}?{query}", self.activity.path())
+        }
+    }
+
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut chars = value.bytes();
+    while let Some(b) = chars.next() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [chars.next()?, chars.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Step {
+    Visit,
+    Back,
+    Forward,
+}
+
+/// Routes visited before and after the current one.
+///
+/// History changes once the shell reflects the activity, a refused
+/// transition leaves it as it was.
+#[derive(Default, Debug)]
+pub struct History {
+    pub current: Route,
+    back: Vec<Route>,
+    forward: Vec<Route>,
+    pending: Option<(Route, Step)>,
+}
+
+impl History {
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Activity to go to for a back step, if there is one.
+    pub fn go_back(&mut self) -> Option<Activity> {
+        let route = self.back.last()?.clone();
+        self.expect(route, Step::Back)
+    }
+
+    /// Activity to go to for a forward step, if there is one.
+    pub fn go_forward(&mut self) -> Option<Activity> {
+        let route = self.forward.last()?.clone();
+        self.expect(route, Step::Forward)
+    }
+
+    /// Activity to go to for a link, its parameters apply on arrival.
+    pub fn open(&mut self, route: Route) -> Option<Activity> {
+        if route.activity == self.current.activity {
+            self.current = route;
+            None
+        } else {
+            self.expect(route, Step::Visit)
+        }
+    }
+
+    /// Records arriving at `activity`.
+    ///
+    /// Arriving unasked at the route right behind or ahead is a step back or
+    /// forward, as with the browser buttons. Visiting a route further behind
+    /// goes back to it, so returning to the intro doesn't pile up steps.
+    pub fn arrive(&mut self, activity: Activity) {
+        let at = |routes: &[Route]| routes.last().filter(|r| r.activity == activity).cloned();
+        let (to, step) = match self.pending.take() {
+            Some((route, step)) if route.activity == activity => (route, step),
+            _ => match (at(&self.back), at(&self.forward)) {
+                (Some(route), _) => (route, Step::Back),
+                (None, Some(route)) => (route, Step::Forward),
+                (None, None) => (Route::new(activity), Step::Visit),
+            },
+        };
+
+        if to.activity == self.current.activity {
+            return;
+        }
+
+        let from = std::mem::replace(&mut self.current, to);
+        match step {
+            Step::Back => {
+                self.back.pop();
+                self.forward.push(from);
+            }
+            Step::Forward => {
+                self.forward.pop();
+                self.back.push(from);
+            }
+            Step::Visit => {
+                let current = self.current.activity;
+                match self.back.iter().position(|r| r.activity == current) {
+                    Some(at) => self.back.truncate(at),
+                    None => self.back.push(from),
+                }
+                self.forward.clear();
+            }
+        }
+    }
+
+    fn expect(&mut self, route: Route, step: Step) -> Option<Activity> {
+        let activity = route.activity;
+        self.pending = Some((route, step));
+        Some(activity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_deep_links() {
+        let route = Route::parse("/tune?preset=street").unwrap();
+        assert_eq!(route.activity, Activity::Tune);
+        assert_eq!(route.param("preset"), Some("street"));
+
+        let route = Route::parse("https://example.com/play/?name=red%20siren#top").unwrap();
+        assert_eq!(route.activity, Activity::Play);
+        assert_eq!(route.param("name"), Some("red siren"));
+        assert_eq!(route.path(), "/play?name=red%20siren");
+
+        assert_eq!(
+            Route::parse("redsiren://about").unwrap().activity,
+            Activity::About
+        );
+        assert_eq!(Route::parse("").unwrap(), Route::new(Activity::Intro));
+        assert_eq!(
+            Route::parse("/nowhere"),
+            Err(RouteError::UnknownPath("/nowhere".to_string()))
+        );
+        assert_eq!(
+            Route::parse("/tune?preset=%2"),
+            Err(RouteError::Param("preset=%2".to_string()))
+        );
+    }
+
+    #[test]
+    fn steps_back_and_forward() {
+        let mut history = History::default();
+        history.arrive(Activity::Tune);
+        history.arrive(Activity::Play);
+        assert!(history.can_go_back());
+        assert!(!history.can_go_forward());
+
+        assert_eq!(history.go_back(), Some(Activity::Tune));
+        history.arrive(Activity::Tune);
+        assert_eq!(history.current.activity, Activity::Tune);
+        assert!(history.can_go_forward());
+
+        assert_eq!(history.go_forward(), Some(Activity::Play));
+        history.arrive(Activity::Play);
+        assert!(!history.can_go_forward());
+
+        history.arrive(Activity::Intro);
+        assert!(!history.can_go_back());
+        assert_eq!(history.go_back(), None);
+    }
+
+    #[test]
+    fn follows_browser_back_and_forward() {
+        let mut history = History::default();
+        history.open(Route::parse("/tune?preset=street").unwrap());
+        history.arrive(Activity::Tune);
+        history.arrive(Activity::Play);
+
+        history.arrive(Activity::Tune);
+        assert!(history.can_go_forward());
+        assert_eq!(history.current.param("preset"), Some("street"));
+        history.arrive(Activity::Intro);
+        assert!(!history.can_go_back());
+        assert_eq!(history.forward.len(), 2);
+
+        history.arrive(Activity::Tune);
+        history.arrive(Activity::Play);
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back.len(), 2);
+    }
+
+    #[test]
+    fn opens_links_on_arrival() {
+        let mut history = History::default();
+        let route = Route::parse("/tune?preset=street").unwrap();
+        assert_eq!(history.open(route.clone()), Some(Activity::Tune));
+
+        history.arrive(Activity::Tune);
+        assert_eq!(history.current, route);
+
+        let same = Route::parse("/tune?preset=hall").unwrap();
+        assert_eq!(history.open(same), None);
+        assert_eq!(history.current.param("preset"), Some("hall"));
+
+        let refused = history.go_back();
+        assert_eq!(refused, Some(Activity::Intro));
+        history.arrive(Activity::Tune);
+        assert!(history.can_go_back());
+    }
+}
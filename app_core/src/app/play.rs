@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crux_core::capability::{CapabilityContext, Operation};
 use crux_macros::Capability;
 use serde::{Deserialize, Serialize};
@@ -17,11 +19,132 @@ pub enum PlayOperation {
     QueryOutputDevices,
     Config(Config, Vec<Node>, Vec<TuningValue>),
     Input(Vec<Vec<f32>>),
-    SendSnoops
+    Subscribe { stream: Stream, rate_hz: f32 },
+    Unsubscribe(Stream),
+    MasterBus(MasterBus),
+    LoadSample(usize, Vec<u8>),
+    LoadSamplePath(usize, String),
+    Effects(Effects),
+    NodeMetering(NodeMetering),
+    NodeGains(Vec<(usize, f32)>),
 }
 
 impl Eq for PlayOperation {}
 
+/// Data the audio core can push to the shell while playing.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum Stream {
+    String,
+    NodeLevels,
+    MasterLevels,
+    Spectrum,
+}
+
+/// Streams a view wants pushed, with their rates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Subscriptions(pub HashMap<Stream, f32>);
+
+impl Default for Subscriptions {
+    fn default() -> Self {
+        Self(HashMap::from([
+            (Stream::String, 24.0),
+            (Stream::NodeLevels, 30.0),
+            (Stream::MasterLevels, 24.0),
+        ]))
+    }
+}
+
+/// Dynamics applied to the mixed output before it reaches the device.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MasterBus {
+    pub threshold_db: f32,
+    pub ratio: f32,
+    pub attack: f32,
+    pub release: f32,
+    pub ceiling_db: f32,
+}
+
+impl Eq for MasterBus {}
+
+impl Default for MasterBus {
+    fn default() -> Self {
+        Self {
+            threshold_db: -18.0,
+            ratio: 4.0,
+            attack: 0.01,
+            release: 0.2,
+            ceiling_db: -1.0,
+        }
+    }
+}
+
+/// Send effects mixed on top of the dry node output.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Effects {
+    pub reverb_wet: f32,
+    pub reverb_room: f32,
+    pub reverb_time: f32,
+    pub delay_wet: f32,
+    pub delay_time: f32,
+    pub delay_feedback: f32,
+    pub chorus_wet: f32,
+}
+
+impl Eq for Effects {}
+
+impl Default for Effects {
+    fn default() -> Self {
+        Self {
+            reverb_wet: 0.0,
+            reverb_room: 20.0,
+            reverb_time: 2.0,
+            delay_wet: 0.0,
+            delay_time: 0.35,
+            delay_feedback: 0.3,
+            chorus_wet: 0.0,
+        }
+    }
+}
+
+/// Envelope follower timing of the node meters.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct NodeMetering {
+    pub attack: f32,
+    pub release: f32,
+}
+
+impl Eq for NodeMetering {}
+
+impl Default for NodeMetering {
+    fn default() -> Self {
+        Self {
+            attack: 0.005,
+            release: 0.15,
+        }
+    }
+}
+
+/// Output level of a node, linear scale.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct NodeLevel {
+    pub f_n: usize,
+    pub rms: f32,
+    pub peak: f32,
+    pub envelope: f32,
+}
+
+impl Eq for NodeLevel {}
+
+/// Output level of a channel since the previous reading, linear scale.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct Level {
+    pub rms: f32,
+    pub peak: f32,
+    pub reduction_db: f32,
+}
+
+impl Eq for Level {}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub enum PlayOperationOutput {
     Success,
@@ -41,7 +164,9 @@ impl Operation for PlayOperationOutput {
 pub enum CaptureOutput {
     CaptureFFT(Vec<(f32, f32)>),
     CaptureData(Vec<f32>),
-    CaptureNodesData(Vec<(usize, Vec<f32>)>)
+    CaptureLevels(Vec<Level>),
+    NodeLevels(Vec<NodeLevel>),
+    Spectrum(Vec<(f32, f32)>),
 }
 
 impl Eq for CaptureOutput {}
@@ -107,7 +232,7 @@ where
         })
     }
 
-    pub fn install_au<F>(&self, f: F)
+    pub fn master_bus<F>(&self, settings: MasterBus, f: F)
     where
         Ev: 'static,
         F: Fn(bool) -> Ev + Send + 'static,
@@ -115,17 +240,128 @@ where
         let ctx = self.context.clone();
 
         self.context.spawn(async move {
-            let done = ctx.request_from_shell(PlayOperation::InstallAU).await;
+            let done = ctx
+                .request_from_shell(PlayOperation::MasterBus(settings))
+                .await;
             ctx.update_app(f(done == PlayOperationOutput::Success));
         })
     }
-    
-    pub fn query_snoops(&self)
+
+    pub fn effects<F>(&self, settings: Effects, f: F)
+    where
+        Ev: 'static,
+        F: Fn(bool) -> Ev + Send + 'static,
     {
         let ctx = self.context.clone();
 
         self.context.spawn(async move {
-            ctx.notify_shell(PlayOperation::SendSnoops).await;
+            let done = ctx
+                .request_from_shell(PlayOperation::Effects(settings))
+                .await;
+            ctx.update_app(f(done == PlayOperationOutput::Success));
+        })
+    }
+
+    pub fn node_metering<F>(&self, settings: NodeMetering, f: F)
+    where
+        Ev: 'static,
+        F: Fn(bool) -> Ev + Send + 'static,
+    {
+        let ctx = self.context.clone();
+
+        self.context.spawn(async move {
+            let done = ctx
+                .request_from_shell(PlayOperation::NodeMetering(settings))
+                .await;
+            ctx.update_app(f(done == PlayOperationOutput::Success));
+        })
+    }
+
+    pub fn node_gains<F>(&self, gains: Vec<(usize, f32)>, f: F)
+    where
+        Ev: 'static,
+        F: Fn(bool) -> Ev + Send + 'static,
+    {
+        let ctx = self.context.clone();
+
+        self.context.spawn(async move {
+            let done = ctx
+                .request_from_shell(PlayOperation::NodeGains(gains))
+                .await;
+            ctx.update_app(f(done == PlayOperationOutput::Success));
+        })
+    }
+
+    pub fn subscribe<F>(&self, stream: Stream, rate_hz: f32, f: F)
+    where
+        Ev: 'static,
+        F: Fn(bool) -> Ev + Send + 'static,
+    {
+        let ctx = self.context.clone();
+
+        self.context.spawn(async move {
+            let done = ctx
+                .request_from_shell(PlayOperation::Subscribe { stream, rate_hz })
+                .await;
+            ctx.update_app(f(done == PlayOperationOutput::Success));
+        })
+    }
+
+    pub fn unsubscribe<F>(&self, stream: Stream, f: F)
+    where
+        Ev: 'static,
+        F: Fn(bool) -> Ev + Send + 'static,
+    {
+        let ctx = self.context.clone();
+
+        self.context.spawn(async move {
+            let done = ctx
+                .request_from_shell(PlayOperation::Unsubscribe(stream))
+                .await;
+            ctx.update_app(f(done == PlayOperationOutput::Success));
+        })
+    }
+
+    pub fn load_sample<F>(&self, slot: usize, bytes: Vec<u8>, f: F)
+    where
+        Ev: 'static,
+        F: Fn(bool) -> Ev + Send + 'static,
+    {
+        let ctx = self.context.clone();
+
+        self.context.spawn(async move {
+            let done = ctx
+                .request_from_shell(PlayOperation::LoadSample(slot, bytes))
+                .await;
+            ctx.update_app(f(done == PlayOperationOutput::Success));
+        })
+    }
+
+    pub fn load_sample_path<F>(&self, slot: usize, path: String, f: F)
+    where
+        Ev: 'static,
+        F: Fn(bool) -> Ev + Send + 'static,
+    {
+        let ctx = self.context.clone();
+
+        self.context.spawn(async move {
+            let done = ctx
+                .request_from_shell(PlayOperation::LoadSamplePath(slot, path))
+                .await;
+            ctx.update_app(f(done == PlayOperationOutput::Success));
+        })
+    }
+
+    pub fn install_au<F>(&self, f: F)
+    where
+        Ev: 'static,
+        F: Fn(bool) -> Ev + Send + 'static,
+    {
+        let ctx = self.context.clone();
+
+        self.context.spawn(async move {
+            let done = ctx.request_from_shell(PlayOperation::InstallAU).await;
+            ctx.update_app(f(done == PlayOperationOutput::Success));
         })
     }
 
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
 use crux_core::render::Render;
@@ -9,6 +10,7 @@ use mint::Point2;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    animate::{follow, Animate, Clock, ClockEV, SETTLED, TUNER_ANIMATION},
     geometry::{Line, Rect},
     instrument::{self, layout::MenuPosition},
     Navigate, Play,
@@ -45,6 +47,10 @@ pub struct Model {
     pub tuning: Option<Vec<TuningValue>>,
     pub state: State,
     pub menu_position: MenuPosition,
+    pub reduced_motion: bool,
+    pub clock: Clock,
+    /// Rects pairs are shown at while they ease to where they were moved.
+    pub moving: BTreeMap<usize, Rect>,
 }
 
 impl Model {
@@ -76,6 +82,8 @@ pub enum TunerEV {
     MovementXY((f64, f64), i32),
     ActivationXY((f64, f64), i32),
     DeactivationXY(i32),
+    /// Moves the pair by `(dx, dy)` on the screen, a drag without a finger.
+    NudgePair(usize, (f64, f64)),
     SetConfig(instrument::Config),
     Activate(bool),
     FftData(Vec<(f32, f32)>),
@@ -85,6 +93,10 @@ pub enum TunerEV {
     PlayOpStopCapturing(bool),
     PlayOpPermission(bool),
     PlayOpInstall(bool),
+    /// Moves pairs straight to where they were moved instead of easing them.
+    SetReducedMotion(bool),
+    TsNext(f64),
+    Clock(ClockEV),
 }
 
 impl Eq for TunerEV {}
@@ -97,6 +109,7 @@ pub struct TunerCapabilities {
     pub key_value: KeyValue<TunerEV>,
     pub play: Play<TunerEV>,
     pub navigate: Navigate<TunerEV>,
+    pub animate: Animate<TunerEV>,
 }
 
 impl App for Tuner {
@@ -124,6 +137,7 @@ impl App for Tuner {
                     model.config = config;
                     model.chart = Some(Chart::new(&mut world, &model.config));
                 }
+                model.moving.clear();
 
                 self.update_pairs_from_values(model);
 
@@ -234,13 +248,8 @@ impl App for Tuner {
                     if let Some(mut pair) = model
                         .chart
                         .as_ref()
-                        .map(|ch| {
-                            ch.pairs
-                                .iter()
-                                .map(|e| world.get::<&mut Pair>(*e).expect("Pair for entity"))
-                                .find(|p| p.rect.contains(Point2 { x, y }))
-                        })
-                        .flatten()
+                        .and_then(|ch| ch.hit(&world, Point2 { x, y }, &model.moving))
+                        .map(|e| world.get::<&mut Pair>(e).expect("Pair for entity"))
                     {
                         pair.finger = Some(id);
                     };
@@ -288,7 +297,8 @@ impl App for Tuner {
                         .flatten();
 
                     if let Some(f_n) = f_n {
-                        model.chart.as_ref().unwrap().update_value_from_pos(
+                        model.moving.remove(&f_n);
+                        model.chart.as_mut().unwrap().update_value_from_pos(
                             &mut world,
                             f_n,
                             (&x, &y),
@@ -298,13 +308,67 @@ impl App for Tuner {
                 }
                 caps.render.render();
             }
+            TunerEV::NudgePair(f_n, (dx, dy)) => {
+                self.move_pairs(model, caps, |model| {
+                    let mut world = model.world.lock().expect("world lock");
+                    let center = world
+                        .query::<&Pair>()
+                        .into_iter()
+                        .find(|(_, p)| p.f_n == f_n)
+                        .map(|(_, p)| p.rect.center());
+
+                    if let Some((chart, center)) = model.chart.as_mut().zip(center) {
+                        chart.update_value_from_pos(
+                            &mut world,
+                            f_n,
+                            (&(center.x + dx), &(center.y + dy)),
+                            &model.config,
+                        );
+                    } else {
+                        log::warn!("no pair for fn {f_n}");
+                    }
+                });
+                caps.render.render();
+            }
+            TunerEV::SetReducedMotion(reduced_motion) => {
+                model.reduced_motion = reduced_motion;
+                if reduced_motion && !model.moving.is_empty() {
+                    model.moving.clear();
+                    caps.animate.stop(TUNER_ANIMATION);
+                }
+                caps.render.render();
+            }
+            TunerEV::TsNext(ts) => {
+                let dt = model.clock.step(ts);
+                let targets = self
+                    .pairs(model)
+                    .into_iter()
+                    .map(|p| (p.f_n, p.rect))
+                    .collect::<BTreeMap<_, _>>();
+
+                model.moving.retain(|f_n, rect| match targets.get(f_n) {
+                    Some(to) => {
+                        *rect = follow(*rect, *to, dt);
+                        let (at, to) = (rect.center(), to.center());
+                        (at.x - to.x).hypot(at.y - to.y) > SETTLED
+                    }
+                    None => false,
+                });
+
+                if model.moving.is_empty() {
+                    caps.animate.stop(TUNER_ANIMATION);
+                }
+                caps.render.render();
+            }
+            TunerEV::Clock(event) => model.clock.update(event),
             TunerEV::TuningKV(kv) => match kv {
                 KeyValueOutput::Read(value) => {
                     model.persisted = value.is_some();
                     model.tuning = value
                         .map(|d| bincode::deserialize::<Vec<TuningValue>>(d.as_slice()).ok())
                         .flatten();
-                    self.update_pairs_from_values(model);
+                    self.move_pairs(model, caps, |model| self.update_pairs_from_values(model));
+                    caps.render.render();
                 }
                 KeyValueOutput::Write(success) => model.persisted = success,
             },
@@ -336,7 +400,51 @@ impl Tuner {
         }
     }
 
-    fn get_pairs(&self, model: &Model) -> Vec<Pair> {
+    /// Pairs as shown, at their eased rects while moving.
+    pub fn get_pairs(&self, model: &Model) -> Vec<Pair> {
+        self.pairs(model)
+            .into_iter()
+            .map(|p| Pair {
+                rect: model.moving.get(&p.f_n).copied().unwrap_or(p.rect),
+                ..p
+            })
+            .collect()
+    }
+
+    /// Runs `change` and eases the pairs it moved from where they were shown.
+    fn move_pairs(
+        &self,
+        model: &mut Model,
+        caps: &TunerCapabilities,
+        change: impl FnOnce(&mut Model),
+    ) {
+        let shown = self
+            .get_pairs(model)
+            .into_iter()
+            .map(|p| (p.f_n, p.rect))
+            .collect::<BTreeMap<_, _>>();
+        let was_moving = !model.moving.is_empty();
+
+        change(model);
+
+        if model.reduced_motion {
+            return;
+        }
+
+        for pair in self.pairs(model) {
+            if let Some(rect) = shown.get(&pair.f_n).filter(|r| **r != pair.rect) {
+                model.moving.insert(pair.f_n, *rect);
+            }
+        }
+
+        if !was_moving && !model.moving.is_empty() {
+            model.clock.restart();
+            caps.animate
+                .start(TUNER_ANIMATION, TunerEV::TsNext, "pairs".to_string());
+        }
+    }
+
+    fn pairs(&self, model: &Model) -> Vec<Pair> {
         let world = model.world.lock().expect("world lock");
         model
             .chart
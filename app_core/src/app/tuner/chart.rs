@@ -1,10 +1,11 @@
 use crate::{
-    geometry::{Line, Rect},
+    geometry::{Line, Rect, SpatialIndex},
     instrument::Config,
 };
 use hecs::{Bundle, Entity, World};
 use mint::Point2;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use super::{TuningValue, MAX_F, MIN_F};
 
@@ -119,6 +120,8 @@ pub struct Chart {
     pub fft_values: Vec<Entity>,
     pub line: Line,
     pub scale: f64,
+    /// Pair rects by entity, for hit-testing pointers.
+    pub index: SpatialIndex<Entity>,
 }
 
 impl Chart {
@@ -128,6 +131,11 @@ impl Chart {
             pairs.push(Pair::spawn(world, config, i));
         }
         pairs.reverse();
+        let mut index = SpatialIndex::new(config.button_size * 2.0);
+        for e in pairs.iter() {
+            let pair = world.get::<&Pair>(*e).expect("pair");
+            index.insert(*e, pair.rect);
+        }
         let min_y = config.height - config.safe_area[3];
         let line = Line::new(0.0, config.width, min_y, min_y);
         Chart {
@@ -135,6 +143,7 @@ impl Chart {
             fft_values: Default::default(),
             line,
             scale: 1.0,
+            index,
         }
     }
 
@@ -190,8 +199,33 @@ impl Chart {
         }
     }
 
-    pub fn update_pairs_from_values(
+    /// Pair shown under `pt`, if any. Pairs in `moving` are shown at
+    /// their eased rect rather than at the indexed one they ease to.
+    pub fn hit(
         &self,
+        world: &World,
+        pt: Point2<f64>,
+        moving: &BTreeMap<usize, Rect>,
+    ) -> Option<Entity> {
+        let f_n = |e: &Entity| world.get::<&Pair>(*e).map(|p| p.f_n).ok();
+
+        self.pairs
+            .iter()
+            .find(|e| {
+                f_n(e)
+                    .and_then(|f_n| moving.get(&f_n))
+                    .is_some_and(|rect| rect.contains(pt))
+            })
+            .copied()
+            .or_else(|| {
+                self.index
+                    .hit(pt)
+                    .find(|e| f_n(e).map_or(true, |f_n| !moving.contains_key(&f_n)))
+            })
+    }
+
+    pub fn update_pairs_from_values(
+        &mut self,
         world: &mut World,
         values: &[TuningValue],
         config: &Config,
@@ -200,13 +234,14 @@ impl Chart {
         for (f_n, value_freq, value_amp) in values {
             let x = config.width - ((*value_freq - MIN_F) / range_width) as f64 * config.width;
             let pt = FFTChartEntry::value_point(x, config, *value_amp);
-            if let Some((_, pair)) = world
+            if let Some((e, pair)) = world
                 .query_mut::<&mut Pair>()
                 .into_iter()
                 .find(|(_, p)| p.f_n == *f_n)
             {
                 pair.rect.move_x(pt.x);
                 pair.rect.move_y(pt.y);
+                self.index.insert(e, pair.rect);
                 pair.value = Some((*value_freq, *value_amp));
                 log::info!("set pair {f_n} to {pt:?}");
             } else {
@@ -216,7 +251,7 @@ impl Chart {
     }
 
     pub fn update_value_from_pos(
-        &self,
+        &mut self,
         world: &mut World,
         f_n: usize,
         (x, y): (&f64, &f64),
@@ -241,7 +276,7 @@ impl Chart {
             .find(|(_, p)| p.f_n == f_n + 1)
             .map(|(_, p)| p.rect.clone());
 
-        let (_, pair) = world
+        let (e, pair) = world
             .query_mut::<&mut Pair>()
             .into_iter()
             .find(|(_, p)| p.f_n == f_n)
@@ -261,6 +296,31 @@ impl Chart {
             pair.value = Some((value_freq, value_amp));
             pair.rect.move_x(*x);
             pair.rect.move_y(*y);
+            self.index.insert(e, pair.rect);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_pairs_where_they_are_shown() {
+        let mut world = World::new();
+        let config = Config::new(1920.0, 1080.0, 96.0, Default::default());
+        let chart = Chart::new(&mut world, &config);
+
+        let e = chart.pairs[0];
+        let (f_n, rect) = {
+            let pair = world.get::<&Pair>(e).unwrap();
+            (pair.f_n, pair.rect)
+        };
+        let shown = rect.offset_top_and_bottom(-rect.height() * 3.0, rect.height() * 3.0);
+        let moving = BTreeMap::from([(f_n, shown)]);
+
+        assert_eq!(chart.hit(&world, rect.center(), &BTreeMap::new()), Some(e));
+        assert_eq!(chart.hit(&world, shown.center(), &moving), Some(e));
+        assert_eq!(chart.hit(&world, rect.center(), &moving), None);
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use mint::Point2;
+
+use super::rect::Rect;
+
+const DEFAULT_CELL: f64 = 64.0;
+
+/// Grid of buckets over rects, finds what a pointer lands on
+/// without scanning everything.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<K> {
+    cell: f64,
+    cells: HashMap<(i64, i64), Vec<K>>,
+    rects: HashMap<K, Rect>,
+}
+
+impl<K: Copy + Eq + Hash> Default for SpatialIndex<K> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL)
+    }
+}
+
+impl<K: Copy + Eq + Hash> SpatialIndex<K> {
+    /// Index with square buckets of `cell` size, about the size of
+    /// the indexed rects works best.
+    pub fn new(cell: f64) -> Self {
+        Self {
+            cell: if cell.is_finite() && cell > 0.0 {
+                cell
+            } else {
+                DEFAULT_CELL
+            },
+            cells: HashMap::new(),
+            rects: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&Rect> {
+        self.rects.get(key)
+    }
+
+    /// Adds `key` at `rect`, or moves it there.
+    pub fn insert(&mut self, key: K, rect: Rect) {
+        self.remove(&key);
+
+        let (left, right, top, bottom) = rect.components();
+        if ![left, right, top, bottom].iter().all(|c| c.is_finite()) {
+            log::warn!("not indexing unbounded rect {rect:?}");
+            return;
+        }
+
+        for cell in self.cells_of(&rect) {
+            self.cells.entry(cell).or_default().push(key);
+        }
+        self.rects.insert(key, rect);
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<Rect> {
+        let rect = self.rects.remove(key)?;
+        for cell in self.cells_of(&rect) {
+            if let Some(keys) = self.cells.get_mut(&cell) {
+                keys.retain(|k| k != key);
+                if keys.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+        Some(rect)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.rects.clear();
+    }
+
+    /// Keys whose rect contains `pt`, the latest inserted last.
+    pub fn hit(&self, pt: Point2<f64>) -> impl Iterator<Item = K> + '_ {
+        self.cells
+            .get(&self.cell_at(pt))
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |key| self.rects.get(key).map_or(false, |r| r.contains(pt)))
+    }
+
+    fn cell_at(&self, pt: Point2<f64>) -> (i64, i64) {
+        (
+            (pt.x / self.cell).floor() as i64,
+            (pt.y / self.cell).floor() as i64,
+        )
+    }
+
+    fn cells_of(&self, rect: &Rect) -> impl Iterator<Item = (i64, i64)> {
+        let (x0, y0) = self.cell_at(rect.top_left());
+        let (x1, y1) = self.cell_at(rect.bottom_right());
+        (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_moved_and_overlapping_rects() {
+        let mut index = SpatialIndex::new(10.0);
+        index.insert(1, Rect::new(0.0, 100.0, 0.0, 20.0));
+        index.insert(2, Rect::new(40.0, 60.0, 5.0, 15.0));
+        index.insert(3, Rect::new(-30.0, -10.0, -30.0, -10.0));
+
+        let pt = |x, y| Point2 { x, y };
+        assert_eq!(index.hit(pt(50.0, 10.0)).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(index.hit(pt(90.0, 10.0)).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(index.hit(pt(-20.0, -20.0)).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(index.hit(pt(50.0, 30.0)).count(), 0);
+
+        index.insert(2, Rect::new(200.0, 220.0, 200.0, 220.0));
+        assert_eq!(index.hit(pt(50.0, 10.0)).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(index.hit(pt(210.0, 210.0)).collect::<Vec<_>>(), vec![2]);
+
+        assert!(index.remove(&1).is_some());
+        assert_eq!(index.hit(pt(90.0, 10.0)).count(), 0);
+        assert_eq!(index.len(), 2);
+    }
+}
@@ -58,4 +58,26 @@ impl Line {
     pub fn len(&self) -> f64 {
         self.width().max(self.height())
     }
+
+    /// Closest point of the segment to `pt`.
+    pub fn project(&self, pt: Point2<f64>) -> Point2<f64> {
+        let (p0, d_x, d_y) = (self.p0(), self.width(), self.height());
+        let len_sq = d_x * d_x + d_y * d_y;
+        if len_sq == 0.0 {
+            return p0;
+        }
+
+        let t = (((pt.x - p0.x) * d_x + (pt.y - p0.y) * d_y) / len_sq).clamp(0.0, 1.0);
+
+        Point2 {
+            x: p0.x + d_x * t,
+            y: p0.y + d_y * t,
+        }
+    }
+
+    /// Distance from `pt` to the segment.
+    pub fn distance(&self, pt: Point2<f64>) -> f64 {
+        let closest = self.project(pt);
+        (pt.x - closest.x).hypot(pt.y - closest.y)
+    }
 }
@@ -1,5 +1,9 @@
+pub use index::*;
 pub use line::*;
 pub use rect::*;
+pub use transform::*;
 
+pub mod index;
 pub mod line;
 pub mod rect;
+pub mod transform;
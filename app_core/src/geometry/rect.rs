@@ -151,6 +151,49 @@ impl Rect {
         self.rect.y.y -= d;
     }
 
+    /// Overlapping part of both rects, `None` when they only touch or are apart.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let left = self.rect.x.x.max(other.rect.x.x);
+        let right = self.rect.y.x.min(other.rect.y.x);
+        let top = self.rect.x.y.max(other.rect.x.y);
+        let bottom = self.rect.y.y.min(other.rect.y.y);
+
+        if left < right && top < bottom {
+            Some(Self::new(left, right, top, bottom))
+        } else {
+            None
+        }
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Smallest rect holding both.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(
+            self.rect.x.x.min(other.rect.x.x),
+            self.rect.y.x.max(other.rect.y.x),
+            self.rect.x.y.min(other.rect.x.y),
+            self.rect.y.y.max(other.rect.y.y),
+        )
+    }
+
+    /// Moves every side in by `by`, or out when negative. Collapses to
+    /// the center rather than turning inside out.
+    pub fn inset(&self, by: f64) -> Self {
+        let center = self.center();
+        let d_x = (self.width() / 2.0 - by).max(0.0);
+        let d_y = (self.height() / 2.0 - by).max(0.0);
+
+        Self::new(
+            center.x - d_x,
+            center.x + d_x,
+            center.y - d_y,
+            center.y + d_y,
+        )
+    }
+
     pub fn contains(&self, pt: Point2<f64>) -> bool {
         self.top_left().x < pt.x
             && self.top_left().y < pt.y
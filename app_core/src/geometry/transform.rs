@@ -0,0 +1,133 @@
+use mint::Point2;
+
+use super::{line::Line, rect::Rect};
+
+/// Affine transform of points, `x' = a x + c y + tx` and `y' = b x + d y + ty`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    tx: f64,
+    ty: f64,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f64, b: f64, c: f64, d: f64, tx: f64, ty: f64) -> Self {
+        Self { a, b, c, d, tx, ty }
+    }
+
+    pub fn translate(x: f64, y: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub fn scale(x: f64, y: f64) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Turns the x axis towards the y axis by `angle` radians.
+    pub fn rotate(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Swaps the axes, landscape coordinates become portrait ones and back.
+    pub fn transpose() -> Self {
+        Self::new(0.0, 1.0, 1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// `self` followed by `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            tx: next.a * self.tx + next.c * self.ty + next.tx,
+            ty: next.b * self.tx + next.d * self.ty + next.ty,
+        }
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+
+        Some(Self {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + c * self.ty),
+            ty: -(b * self.tx + d * self.ty),
+        })
+    }
+
+    pub fn apply(&self, pt: Point2<f64>) -> Point2<f64> {
+        Point2 {
+            x: self.a * pt.x + self.c * pt.y + self.tx,
+            y: self.b * pt.x + self.d * pt.y + self.ty,
+        }
+    }
+
+    /// Bounding rect of the transformed corners.
+    pub fn apply_rect(&self, rect: &Rect) -> Rect {
+        let [p0, p1, p2, p3] = [
+            rect.top_left(),
+            rect.bottom_right(),
+            rect.top_right(),
+            rect.bottom_left(),
+        ]
+        .map(|pt| self.apply(pt));
+
+        Rect::new(p0.x, p1.x, p0.y, p1.y).union(&Rect::new(p2.x, p3.x, p2.y, p3.y))
+    }
+
+    pub fn apply_line(&self, line: &Line) -> Line {
+        let (p0, p1) = (self.apply(line.p0()), self.apply(line.p1()));
+        Line::new(p0.x, p1.x, p0.y, p1.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transpose_swaps_exactly() {
+        let rect = Rect::new(0.1, 0.7, 1.0 / 3.0, 2.0);
+        let swapped = Transform::transpose().apply_rect(&rect);
+        assert_eq!(swapped, Rect::new(1.0 / 3.0, 2.0, 0.1, 0.7));
+        assert_eq!(Transform::transpose().apply_rect(&swapped), rect);
+        assert_eq!(Transform::IDENTITY.apply_rect(&rect), rect);
+    }
+
+    #[test]
+    fn then_and_inverse_round_trip() {
+        let t = Transform::scale(2.0, 3.0)
+            .then(&Transform::rotate(std::f64::consts::FRAC_PI_2))
+            .then(&Transform::translate(10.0, -5.0));
+        let pt = t.apply(Point2 { x: 1.0, y: 1.0 });
+        assert!(
+            (pt.x - 7.0).abs() < 1.0e-9 && (pt.y + 3.0).abs() < 1.0e-9,
+            "{pt:?}"
+        );
+
+        let back = t.inverse().expect("invertible").apply(pt);
+        assert!((back.x - 1.0).abs() < 1.0e-9 && (back.y - 1.0).abs() < 1.0e-9);
+
+        assert_eq!(Transform::scale(0.0, 1.0).inverse(), None);
+    }
+}
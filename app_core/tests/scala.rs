@@ -0,0 +1,125 @@
+use app_core::instrument::{
+    keyboard::{self, Keyboard},
+    scala::{KeyboardMapping, ScalaError, Scale},
+    Config, TuningSystem,
+};
+use hecs::World;
+
+const MEANQUAR: &str = include_str!("scala/meanquar.scl");
+const PYTH_12: &str = include_str!("scala/pyth_12.scl");
+const BOHLEN_P: &str = include_str!("scala/bohlen-p.scl");
+const WHITEKEYS: &str = include_str!("scala/whitekeys.kbm");
+const LINEAR_A440: &str = include_str!("scala/linear_a440.kbm");
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1.0e-4, "{a} != {b}");
+}
+
+#[test]
+fn parses_scale_corpus() {
+    for (text, size, period) in [(MEANQUAR, 12, 2.0), (PYTH_12, 12, 2.0), (BOHLEN_P, 13, 3.0)] {
+        let scale = Scale::parse(text).expect("parse scale");
+        assert!(!scale.description().is_empty());
+        assert_eq!(scale.len(), size);
+        assert_close(scale.ratio(size as i64), period);
+        assert_eq!(scale.ratio(0), 1.0);
+    }
+
+    let meanquar = Scale::parse(MEANQUAR).unwrap();
+    assert_eq!(
+        meanquar.description(),
+        "1/4-comma meantone scale. Pietro Aaron's temperament (1523)"
+    );
+    assert_eq!(meanquar.ratio(4), 1.25);
+    assert_eq!(meanquar.ratio(16), 2.5);
+    assert_close(meanquar.ratio(-3), 0.5 * 2_f64.powf(889.73529 / 1200.0));
+}
+
+#[test]
+fn parses_dos_line_endings() {
+    let scale = Scale::parse(&PYTH_12.replace('\n', "\r\n")).unwrap();
+    assert_eq!(scale, Scale::parse(PYTH_12).unwrap());
+    assert_eq!(scale.description(), "12-tone Pythagorean scale");
+    assert_eq!(scale.ratio(7), 1.5);
+}
+
+#[test]
+fn reports_truncated_corpus_files() {
+    let truncated = MEANQUAR.trim_end().trim_end_matches("2/1");
+    assert_eq!(
+        Scale::parse(truncated),
+        Err(ScalaError::CountMismatch {
+            expected: 12,
+            found: 11
+        })
+    );
+    assert_eq!(
+        Scale::parse(truncated).unwrap_err().to_string(),
+        "expected 12 pitches, found 11"
+    );
+}
+
+#[test]
+fn maps_white_keys_from_middle_c() {
+    let scale = Scale::parse(PYTH_12).unwrap();
+    let mapping = KeyboardMapping::parse(WHITEKEYS).unwrap();
+    assert_eq!(mapping.middle_note, 60);
+    assert_eq!(mapping.keys.iter().flatten().count(), 7);
+
+    let TuningSystem::Scala { root_hz, ratios } = scale.tuning_system(Some(&mapping)).unwrap()
+    else {
+        panic!("not a scala tuning system");
+    };
+
+    assert_close(root_hz.unwrap() as f64, 440.0 * 16.0 / 27.0);
+    assert_eq!(
+        ratios,
+        vec![9.0 / 8.0, 81.0 / 64.0, 4.0 / 3.0, 3.0 / 2.0, 27.0 / 16.0, 243.0 / 128.0, 2.0]
+    );
+}
+
+#[test]
+fn maps_linear_keyboard_with_a440() {
+    let scale = Scale::parse(MEANQUAR).unwrap();
+    let mapping = KeyboardMapping::parse(LINEAR_A440).unwrap();
+    assert!(mapping.keys.is_empty());
+
+    let TuningSystem::Scala { root_hz, .. } = scale.tuning_system(Some(&mapping)).unwrap() else {
+        panic!("not a scala tuning system");
+    };
+
+    assert_close(
+        root_hz.unwrap() as f64,
+        440.0 / 2_f64.powf(889.73529 / 1200.0),
+    );
+}
+
+#[test]
+fn assigns_degrees_to_buttons_by_f_n() {
+    let scale = Scale::parse(PYTH_12).unwrap();
+    let mapping = KeyboardMapping::parse(WHITEKEYS).unwrap();
+    let tuning_system = scale.tuning_system(Some(&mapping)).unwrap();
+
+    let config = Config {
+        tuning_system: tuning_system.clone(),
+        ..Config::new(430.0, 932.0, 476.0, Default::default())
+    };
+    let mut world = World::new();
+    Keyboard::spawn(&mut world, &config);
+
+    let mut buttons = world
+        .query::<&keyboard::Button>()
+        .iter()
+        .map(|(_, button)| (button.f_n, button.freq))
+        .collect::<Vec<_>>();
+    buttons.sort_by_key(|(f_n, _)| *f_n);
+
+    assert_eq!(buttons.len(), config.n_buttons);
+    assert_close(buttons[0].1 as f64, 440.0 * 16.0 / 27.0);
+    for (f_n, freq) in buttons.iter() {
+        assert_eq!(*freq, tuning_system.band(config.f0, *f_n).0);
+    }
+    for pair in buttons.windows(2) {
+        assert!(pair[0].1 < pair[1].1, "{pair:?} not ascending");
+    }
+}
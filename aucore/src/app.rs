@@ -1,6 +1,6 @@
 use app_core::{
-    instrument::{Config, Node},
-    play::PlayOperation,
+    instrument::{node::node_gains, Config, Node},
+    play::{Effects, NodeMetering, PlayOperation, Stream},
     tuner::{TuningValue, MAX_F, MIN_F},
 };
 use crux_core::render::Render;
@@ -15,25 +15,60 @@ use spectrum_analyzer::{
     FrequencyLimit,
 };
 
-use crate::{capture::Capture, system::SAMPLE_RATE};
+use crate::{
+    bus::Master,
+    capture::Capture,
+    sample::{Sample, SampleBank},
+    subscription::Subscriptions,
+    system::SAMPLE_RATE,
+    window,
+};
 
 use super::resolve::Resolve;
 use super::system::System;
 
 const ANALYZE_SAMPLES_COUNT: usize = 4096;
 
-#[derive(Default)]
 pub struct Model {
     system: Option<System>,
+    master: Master,
     config: Config,
     nodes: Vec<Node>,
+    node_gains: Vec<(usize, f32)>,
     tuning: Vec<TuningValue>,
+    samples: SampleBank,
+    effects: Effects,
+    metering: NodeMetering,
+    subscriptions: Subscriptions,
     audio_data: Vec<Vec<f32>>,
     analyze_samples: Vec<f32>,
+    output_samples: Vec<f32>,
     frame_size: usize,
     capturing: bool,
 }
 
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            system: None,
+            master: Master::new(Default::default(), SAMPLE_RATE),
+            config: Default::default(),
+            nodes: Default::default(),
+            node_gains: Default::default(),
+            tuning: Default::default(),
+            samples: Default::default(),
+            effects: Default::default(),
+            metering: Default::default(),
+            subscriptions: Subscriptions::new(SAMPLE_RATE),
+            audio_data: Default::default(),
+            analyze_samples: Default::default(),
+            output_samples: Default::default(),
+            frame_size: Default::default(),
+            capturing: Default::default(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct ViewModel(pub Vec<Vec<f32>>);
 
@@ -60,17 +95,37 @@ impl App for RedSirenAU {
         match msg {
             PlayOperation::Config(config, nodes, tuning) => {
                 model.config = config;
+                model.node_gains = node_gains(nodes.as_slice());
                 model.nodes = nodes;
                 model.tuning = tuning;
-                _ = model.system.insert(System::new(
-                    model.nodes.as_slice(),
-                    &model.config,
-                    model.tuning.as_slice(),
-                ));
+                self.rebuild_system(model);
 
                 caps.render.render();
                 caps.resolve.resolve_success(true);
             }
+            PlayOperation::Effects(effects) => {
+                model.effects = effects;
+                match model.system.as_ref() {
+                    Some(sys) if sys.sends.needs_rebuild(&model.effects) => {
+                        self.rebuild_system(model);
+                    }
+                    Some(sys) => sys.sends.set(&model.effects),
+                    None => {}
+                }
+                caps.resolve.resolve_success(true);
+            }
+            PlayOperation::LoadSample(slot, bytes) => {
+                self.load_sample(slot, Sample::decode(bytes), model, caps);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            PlayOperation::LoadSamplePath(slot, path) => {
+                self.load_sample(slot, Sample::open(path.as_str()), model, caps);
+            }
+            #[cfg(target_arch = "wasm32")]
+            PlayOperation::LoadSamplePath(slot, _) => {
+                log::warn!("sample {slot} paths are not available in the browser");
+                caps.resolve.resolve_success(false);
+            }
             PlayOperation::Input(input) => {
                 if model.capturing {
                     let data = input.first().cloned().unwrap_or(vec![]);
@@ -79,22 +134,7 @@ impl App for RedSirenAU {
                     } else {
                         let samples = std::mem::replace(&mut model.analyze_samples, data);
 
-                        let hann_window = hann_window(samples.as_slice());
-
-                        let spectrum_hann_window = samples_fft_to_spectrum(
-                            &hann_window,
-                            SAMPLE_RATE as u32,
-                            FrequencyLimit::Range(MIN_F, MAX_F),
-                            Some(&divide_by_N_sqrt),
-                        )
-                        .unwrap();
-
-                        caps.capture.capture_fft(Vec::from_iter(
-                            spectrum_hann_window
-                                .data()
-                                .iter()
-                                .map(|(freq, value)| (freq.val(), value.val())),
-                        ));
+                        caps.capture.capture_fft(spectrum(samples.as_slice()));
                     }
                 } else if let Some(sys) = model.system.as_mut() {
                     let frame_size = input.first().map_or(0, |ch| ch.len());
@@ -125,38 +165,78 @@ impl App for RedSirenAU {
                     sys.net_be
                         .process(model.frame_size, input.as_slice(), output.as_mut_slice());
 
-                    caps.render.render();
-                } else {
-                    log::warn!("skipping new data, no system yet, nor capturing");
-                }
-            }
-            PlayOperation::SendSnoops => {
-                if let Some(sys) = model.system.as_mut() {
-                    if let Some(snp) = sys.out_snp.get() {
-                        let mut data = Vec::new();
-                        for i in 0..snp.size() {
-                            data.push(snp.at(i))
+                    model.master.process(model.audio_data.as_mut_slice());
+
+                    if model.subscriptions.is_subscribed(Stream::Spectrum) {
+                        if let Some(out) = model.audio_data.first() {
+                            model.output_samples.extend_from_slice(out.as_slice());
+                            let excess =
+                                model.output_samples.len().saturating_sub(ANALYZE_SAMPLES_COUNT);
+                            model.output_samples.drain(..excess);
                         }
-                        caps.capture.capture_data(data);
                     }
 
-                    let mut datasets = vec![];
-                    for (snp, f_n) in sys
-                        .node_snp
-                        .iter_mut()
-                        .filter_map(|(s, f_n)| s.get().zip(Some(f_n)))
-                    {
-                        let mut data = Vec::new();
-                        for i in 0..snp.size() {
-                            data.push(snp.at(i))
+                    for stream in model.subscriptions.tick(model.frame_size) {
+                        match stream {
+                            Stream::String => {
+                                if let Some(snp) = sys.out_snp.get() {
+                                    let data = (0..snp.size())
+                                        .map(|i| snp.at(i))
+                                        .collect::<Vec<_>>();
+                                    let points = window::decimate(data.as_slice(), &model.config);
+                                    caps.capture.capture_data(points);
+                                }
+                            }
+                            Stream::NodeLevels => {
+                                caps.capture.capture_node_levels(sys.node_levels());
+                            }
+                            Stream::MasterLevels => {
+                                caps.capture.capture_levels(model.master.levels());
+                            }
+                            Stream::Spectrum => {
+                                if model.output_samples.len() == ANALYZE_SAMPLES_COUNT {
+                                    let data = spectrum(model.output_samples.as_slice());
+                                    caps.capture.capture_spectrum(data);
+                                }
+                            }
                         }
-                        datasets.push((*f_n, data));
-                    }
-                    if !datasets.is_empty() {
-                        caps.capture.capture_nodes_data(datasets);
                     }
+
+                    caps.render.render();
+                } else {
+                    log::warn!("skipping new data, no system yet, nor capturing");
                 }
             }
+            PlayOperation::Subscribe { stream, rate_hz } => {
+                let subscribed = model.subscriptions.subscribe(stream, rate_hz);
+                caps.resolve.resolve_success(subscribed);
+            }
+            PlayOperation::Unsubscribe(stream) => {
+                model.subscriptions.unsubscribe(stream);
+                if stream == Stream::Spectrum {
+                    model.output_samples = vec![];
+                }
+                caps.resolve.resolve_success(true);
+            }
+            PlayOperation::NodeMetering(settings) => {
+                let rebuild = settings != model.metering;
+                model.metering = settings;
+                if rebuild && model.system.is_some() {
+                    self.rebuild_system(model);
+                }
+                caps.resolve.resolve_success(true);
+            }
+            PlayOperation::NodeGains(gains) => {
+                if let Some(sys) = model.system.as_ref() {
+                    sys.set_node_gains(gains.as_slice());
+                }
+                model.node_gains = gains;
+                caps.resolve.resolve_success(true);
+            }
+            PlayOperation::MasterBus(settings) => {
+                model.master.set(settings);
+                caps.resolve.resolve_success(true);
+            }
             PlayOperation::Capture(capturing) => {
                 model.capturing = capturing;
                 caps.resolve.resolve_success(true);
@@ -173,5 +253,63 @@ impl App for RedSirenAU {
     }
 }
 
+impl RedSirenAU {
+    fn rebuild_system(&self, model: &mut Model) {
+        let sys = System::new(
+            model.nodes.as_slice(),
+            &model.config,
+            model.tuning.as_slice(),
+            &model.samples,
+            &model.effects,
+            &model.metering,
+        );
+        sys.set_node_gains(model.node_gains.as_slice());
+        _ = model.system.insert(sys);
+    }
+
+    fn load_sample(
+        &self,
+        slot: usize,
+        sample: anyhow::Result<Sample>,
+        model: &mut Model,
+        caps: &RedSirenAUCapabilities,
+    ) {
+        match sample {
+            Ok(sample) => {
+                log::info!("loaded sample {slot}: {} samples", sample.data.len());
+                model.samples.insert(slot, sample);
+
+                if model.system.is_some() {
+                    self.rebuild_system(model);
+                }
+
+                caps.resolve.resolve_success(true);
+            }
+            Err(e) => {
+                log::error!("sample {slot} not loaded: {e:?}");
+                caps.resolve.resolve_success(false);
+            }
+        }
+    }
+}
+
+fn spectrum(samples: &[f32]) -> Vec<(f32, f32)> {
+    let hann_window = hann_window(samples);
+
+    let spectrum_hann_window = samples_fft_to_spectrum(
+        &hann_window,
+        SAMPLE_RATE as u32,
+        FrequencyLimit::Range(MIN_F, MAX_F),
+        Some(&divide_by_N_sqrt),
+    )
+    .unwrap();
+
+    spectrum_hann_window
+        .data()
+        .iter()
+        .map(|(freq, value)| (freq.val(), value.val()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {}
@@ -0,0 +1,164 @@
+use app_core::play::{Level, MasterBus};
+
+use crate::gain::{amp_to_db, db_to_amp};
+
+const MIN_ENVELOPE: f32 = 1.0e-6;
+
+#[derive(Default, Clone, Copy)]
+struct Meter {
+    sum_sq: f64,
+    count: usize,
+    peak: f32,
+    reduction_db: f32,
+}
+
+/// Compressor followed by a brickwall limiter, with per channel metering.
+///
+/// Channels are linked: one envelope drives the gain of all of them,
+/// so the stereo image does not wander under compression.
+pub struct Master {
+    settings: MasterBus,
+    sample_rate: f64,
+    envelope: f32,
+    limit: f32,
+    meters: Vec<Meter>,
+}
+
+impl Master {
+    pub fn new(settings: MasterBus, sample_rate: f64) -> Self {
+        let mut master = Self {
+            settings,
+            sample_rate,
+            envelope: 0.0,
+            limit: 1.0,
+            meters: vec![],
+        };
+        master.set(settings);
+        master
+    }
+
+    pub fn set(&mut self, settings: MasterBus) {
+        self.settings = MasterBus {
+            ratio: settings.ratio.max(1.0),
+            attack: settings.attack.max(0.0),
+            release: settings.release.max(0.0),
+            ceiling_db: settings.ceiling_db.min(0.0),
+            ..settings
+        };
+    }
+
+    pub fn settings(&self) -> MasterBus {
+        self.settings
+    }
+
+    fn coefficient(&self, time: f32) -> f32 {
+        if time > 0.0 {
+            (-1.0 / (time as f64 * self.sample_rate)).exp() as f32
+        } else {
+            0.0
+        }
+    }
+
+    pub fn process(&mut self, data: &mut [Vec<f32>]) {
+        let frames = data.first().map_or(0, |ch| ch.len());
+        if self.meters.len() != data.len() {
+            self.meters = vec![Meter::default(); data.len()];
+        }
+
+        let attack = self.coefficient(self.settings.attack);
+        let release = self.coefficient(self.settings.release);
+        let ceiling = db_to_amp(self.settings.ceiling_db);
+
+        for i in 0..frames {
+            let level = data
+                .iter()
+                .map(|ch| ch[i].abs())
+                .fold(0.0_f32, f32::max);
+
+            let coef = if level > self.envelope {
+                attack
+            } else {
+                release
+            };
+            self.envelope = coef * self.envelope + (1.0 - coef) * level;
+
+            let over = amp_to_db(self.envelope.max(MIN_ENVELOPE)) - self.settings.threshold_db;
+            let reduction_db = if over > 0.0 {
+                over - over / self.settings.ratio
+            } else {
+                0.0
+            };
+            let gain = db_to_amp(-reduction_db);
+
+            let peak = level * gain;
+            let target = if peak > ceiling { ceiling / peak } else { 1.0 };
+            self.limit = if target < self.limit {
+                target
+            } else {
+                release * self.limit + (1.0 - release) * target
+            };
+            let gain = gain * self.limit;
+
+            for (ch, meter) in data.iter_mut().zip(self.meters.iter_mut()) {
+                let sample = (ch[i] * gain).clamp(-ceiling, ceiling);
+                ch[i] = sample;
+
+                meter.sum_sq += (sample * sample) as f64;
+                meter.count += 1;
+                meter.peak = meter.peak.max(sample.abs());
+                meter.reduction_db = meter.reduction_db.max(-amp_to_db(gain));
+            }
+        }
+    }
+
+    /// Levels accumulated since the previous call.
+    pub fn levels(&mut self) -> Vec<Level> {
+        self.meters
+            .iter_mut()
+            .map(|meter| {
+                let meter = std::mem::take(meter);
+                Level {
+                    rms: if meter.count > 0 {
+                        (meter.sum_sq / meter.count as f64).sqrt() as f32
+                    } else {
+                        0.0
+                    },
+                    peak: meter.peak,
+                    reduction_db: meter.reduction_db,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_never_exceeds_ceiling() {
+        let settings = MasterBus::default();
+        let mut master = Master::new(settings, 44100.0);
+        let ceiling = db_to_amp(settings.ceiling_db);
+
+        let mut data = vec![
+            (0..4096).map(|i| (i as f32 * 0.01).sin() * 4.0).collect::<Vec<_>>(),
+            (0..4096).map(|i| (i as f32 * 0.03).cos() * 2.0).collect::<Vec<_>>(),
+        ];
+        master.process(data.as_mut_slice());
+
+        for s in data.iter().flatten() {
+            assert!(s.abs() <= ceiling, "{s} exceeds {ceiling}");
+        }
+
+        let levels = master.levels();
+        assert_eq!(levels.len(), 2);
+        for level in levels {
+            assert!(level.peak <= ceiling);
+            assert!(level.rms > 0.0 && level.rms <= level.peak);
+            assert!(level.reduction_db > 0.0);
+        }
+
+        assert!(master.levels().iter().all(|l| *l == Level::default()));
+    }
+}
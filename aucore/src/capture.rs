@@ -1,6 +1,6 @@
 use crux_core::capability::CapabilityContext;
 use crux_macros::Capability;
-use app_core::play::CaptureOutput;
+use app_core::play::{CaptureOutput, Level, NodeLevel};
 
 
 #[derive(Capability)]
@@ -32,11 +32,27 @@ where
         })
     }
     
-    pub fn capture_nodes_data(&self, captured: Vec<(usize, Vec<f32>)>) {
+    pub fn capture_node_levels(&self, captured: Vec<NodeLevel>) {
         let ctx = self.context.clone();
-        log::debug!("capture_data");
+        log::trace!("capture_node_levels");
+        self.context.spawn(async move {
+            ctx.notify_shell(CaptureOutput::NodeLevels(captured)).await;
+        })
+    }
+
+    pub fn capture_spectrum(&self, captured: Vec<(f32, f32)>) {
+        let ctx = self.context.clone();
+        log::trace!("capture_spectrum");
+        self.context.spawn(async move {
+            ctx.notify_shell(CaptureOutput::Spectrum(captured)).await;
+        })
+    }
+
+    pub fn capture_levels(&self, captured: Vec<Level>) {
+        let ctx = self.context.clone();
+        log::debug!("capture_levels");
         self.context.spawn(async move {
-            ctx.notify_shell(CaptureOutput::CaptureNodesData(captured)).await;
+            ctx.notify_shell(CaptureOutput::CaptureLevels(captured)).await;
         })
     }
 }
@@ -0,0 +1,91 @@
+use app_core::play::Effects;
+use fundsp::hacker32::*;
+
+const MIN_DELAY: f32 = 0.01;
+const MAX_DELAY: f32 = 2.0;
+const RIGHT_DELAY_RATIO: f32 = 0.75;
+const REVERB_DIFFUSION: f32 = 0.5;
+const CHORUS_SEPARATION: f32 = 0.0;
+const CHORUS_VARIATION: f32 = 0.01;
+const CHORUS_MOD_FREQUENCY: f32 = 0.2;
+
+/// Runtime controls of the send effects placed after the node mix.
+///
+/// Reverb room and time are fixed when the graph is built,
+/// everything else follows the shared values.
+pub struct Sends {
+    pub reverb: Shared<f32>,
+    pub delay: Shared<f32>,
+    pub delay_time_l: Shared<f32>,
+    pub delay_time_r: Shared<f32>,
+    pub delay_feedback: Shared<f32>,
+    pub chorus: Shared<f32>,
+    pub reverb_room: f32,
+    pub reverb_time: f32,
+}
+
+impl Sends {
+    pub fn new(settings: &Effects) -> Self {
+        let sends = Self {
+            reverb: shared(0.0),
+            delay: shared(0.0),
+            delay_time_l: shared(MIN_DELAY),
+            delay_time_r: shared(MIN_DELAY),
+            delay_feedback: shared(0.0),
+            chorus: shared(0.0),
+            reverb_room: settings.reverb_room,
+            reverb_time: settings.reverb_time,
+        };
+        sends.set(settings);
+        sends
+    }
+
+    pub fn set(&self, settings: &Effects) {
+        let delay_time = settings.delay_time.clamp(MIN_DELAY, MAX_DELAY);
+
+        self.reverb.set_value(settings.reverb_wet.clamp(0.0, 1.0));
+        self.delay.set_value(settings.delay_wet.clamp(0.0, 1.0));
+        self.delay_time_l.set_value(delay_time);
+        self.delay_time_r.set_value((delay_time * RIGHT_DELAY_RATIO).max(MIN_DELAY));
+        self.delay_feedback.set_value(settings.delay_feedback.clamp(0.0, 0.95));
+        self.chorus.set_value(settings.chorus_wet.clamp(0.0, 1.0));
+    }
+
+    /// Whether applying `settings` needs the graph to be rebuilt.
+    pub fn needs_rebuild(&self, settings: &Effects) -> bool {
+        self.reverb_room != settings.reverb_room || self.reverb_time != settings.reverb_time
+    }
+
+    /// Stereo dry signal with reverb, delay and chorus sends on top.
+    pub fn stereo(&self) -> An<impl AudioNode<Sample = f32, Inputs = U2, Outputs = U2>> {
+        let reverb = reverb_stereo(
+            self.reverb_room.into(),
+            self.reverb_time.into(),
+            REVERB_DIFFUSION.into(),
+        ) * (var(&self.reverb) | var(&self.reverb));
+
+        let delay = (delay_line(&self.delay_time_l, &self.delay_feedback)
+            | delay_line(&self.delay_time_r, &self.delay_feedback))
+            * (var(&self.delay) | var(&self.delay));
+
+        let chorus = (chorus(0, CHORUS_SEPARATION, CHORUS_VARIATION, CHORUS_MOD_FREQUENCY)
+            | chorus(1, CHORUS_SEPARATION, CHORUS_VARIATION, CHORUS_MOD_FREQUENCY))
+            * (var(&self.chorus) | var(&self.chorus));
+
+        multipass::<U2>() & reverb & delay & chorus
+    }
+}
+
+/// Delay with feedback, the second channel of the loop carries the wet signal out.
+fn delay_line(
+    time: &Shared<f32>,
+    feedback_gain: &Shared<f32>,
+) -> An<impl AudioNode<Sample = f32, Inputs = U1, Outputs = U1>> {
+    let line = (pass() | sink())
+        >> (pass() | var(time))
+        >> tap(MIN_DELAY, MAX_DELAY)
+        >> split::<U2>()
+        >> ((pass() * var(feedback_gain)) | pass());
+
+    (pass() | dc(0.0)) >> feedback(line) >> (sink() | pass())
+}
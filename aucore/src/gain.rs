@@ -0,0 +1,70 @@
+use fundsp::hacker32::*;
+
+/// Level a tuned input is brought to before it excites its node.
+pub const TARGET_DB: f32 = -6.0;
+/// Upper bound for very quiet tunings, keeps the noise floor from exploding.
+pub const MAX_GAIN_DB: f32 = 48.0;
+
+const MIN_AMP: f32 = 1.0e-4;
+const LIMITER_ATTACK: f32 = 0.005;
+const LIMITER_RELEASE: f32 = 0.25;
+
+pub fn amp_to_db(amp: f32) -> f32 {
+    20.0 * amp.log10()
+}
+
+pub fn db_to_amp(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
+
+/// Gain in dB for a node tuned to `amp` (0..1 of full scale).
+pub fn sensitivity_db(amp: f32) -> f32 {
+    let amp = if amp.is_finite() {
+        amp.clamp(MIN_AMP, 1.0)
+    } else {
+        MIN_AMP
+    };
+
+    (TARGET_DB - amp_to_db(amp)).min(MAX_GAIN_DB)
+}
+
+/// Linear gain for a node tuned to `amp`, always positive and bounded.
+pub fn sensitivity(amp: f32) -> f32 {
+    db_to_amp(sensitivity_db(amp))
+}
+
+fn soft_clip_fn(x: f32) -> f32 {
+    x.tanh()
+}
+
+/// Saturates smoothly into the open range (-1, 1).
+pub fn soft_clip() -> An<impl AudioNode<Sample = f32, Inputs = U1, Outputs = U1>> {
+    shape_fn(soft_clip_fn)
+}
+
+/// Limiter followed by a soft clipper, used on every node and on the master bus.
+pub fn protect() -> An<impl AudioNode<Sample = f32, Inputs = U1, Outputs = U1>> {
+    limiter((LIMITER_ATTACK, LIMITER_RELEASE)) >> soft_clip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sensitivity_is_bounded_and_monotonic() {
+        let mut prev = f32::MAX;
+        for i in 0..=100 {
+            let amp = i as f32 / 100.0;
+            let db = sensitivity_db(amp);
+            assert!((TARGET_DB..=MAX_GAIN_DB).contains(&db), "{amp}: {db}");
+            assert!(db <= prev, "{amp}: {db} > {prev}");
+            prev = db;
+        }
+
+        assert_eq!(sensitivity_db(f32::NAN), MAX_GAIN_DB);
+        assert_eq!(sensitivity_db(-1.0), MAX_GAIN_DB);
+        assert_eq!(sensitivity_db(2.0), TARGET_DB);
+        assert!(sensitivity(0.0) > 0.0);
+    }
+}
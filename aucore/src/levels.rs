@@ -0,0 +1,55 @@
+use app_core::play::{NodeLevel, NodeMetering};
+use fundsp::hacker32::*;
+
+const RMS_WINDOW: f32 = 0.05;
+const PEAK_RELEASE: f32 = 0.3;
+
+/// Per node envelope followers running on the audio thread.
+pub struct NodeMeter {
+    pub f_n: usize,
+    pub rms: Shared<f32>,
+    pub peak: Shared<f32>,
+    pub envelope: Shared<f32>,
+}
+
+impl NodeMeter {
+    pub fn new(f_n: usize) -> Self {
+        Self {
+            f_n,
+            rms: shared(0.0),
+            peak: shared(0.0),
+            envelope: shared(0.0),
+        }
+    }
+
+    /// Pass-through that keeps the shared levels up to date.
+    pub fn node(
+        &self,
+        metering: &NodeMetering,
+    ) -> An<impl AudioNode<Sample = f32, Inputs = U1, Outputs = U1>> {
+        let rms = shape_fn(|x: f32| x * x)
+            >> follow(RMS_WINDOW)
+            >> shape_fn(|x: f32| x.max(0.0).sqrt())
+            >> monitor(&self.rms, Meter::Sample)
+            >> sink();
+        let peak = shape_fn(f32::abs)
+            >> afollow(0.0, PEAK_RELEASE)
+            >> monitor(&self.peak, Meter::Sample)
+            >> sink();
+        let envelope = shape_fn(f32::abs)
+            >> afollow(metering.attack.max(0.0), metering.release.max(0.0))
+            >> monitor(&self.envelope, Meter::Sample)
+            >> sink();
+
+        split::<U4>() >> (pass() | rms | peak | envelope)
+    }
+
+    pub fn level(&self) -> NodeLevel {
+        NodeLevel {
+            f_n: self.f_n,
+            rms: self.rms.value(),
+            peak: self.peak.value(),
+            envelope: self.envelope.value(),
+        }
+    }
+}
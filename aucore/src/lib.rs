@@ -4,9 +4,17 @@ pub use app::*;
 
 
 pub mod app;
+pub mod bus;
+pub mod effects;
 mod resolve;
 mod capture;
+pub mod gain;
+pub mod levels;
+pub mod sample;
+pub mod subscription;
 pub mod system;
+pub mod voice;
+pub mod window;
 
 
 cfg_if::cfg_if! {if #[cfg(feature="browser")] {
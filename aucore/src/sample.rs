@@ -0,0 +1,190 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use fundsp::hacker32::*;
+
+use crate::system::SAMPLE_RATE;
+
+pub type SampleBank = HashMap<usize, Sample>;
+
+/// Mono sample data at the system sample rate.
+#[derive(Clone)]
+pub struct Sample {
+    pub data: Arc<Vec<f32>>,
+    pub sample_rate: f64,
+}
+
+impl Sample {
+    /// Decodes WAV or FLAC bytes.
+    pub fn decode(bytes: Vec<u8>) -> Result<Self> {
+        let wave = Wave32::load_slice(bytes)?;
+        Self::from_wave(&wave)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: &str) -> Result<Self> {
+        let wave = Wave32::load(path)?;
+        Self::from_wave(&wave)
+    }
+
+    fn from_wave(wave: &Wave32) -> Result<Self> {
+        let channels = wave.channels();
+        if channels == 0 || wave.len() == 0 {
+            return Err(anyhow!("empty sample"));
+        }
+
+        let mono = (0..wave.len())
+            .map(|i| (0..channels).map(|ch| wave.at(ch, i)).sum::<f32>() / channels as f32)
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            data: Arc::new(resample(mono.as_slice(), wave.sample_rate(), SAMPLE_RATE)),
+            sample_rate: SAMPLE_RATE,
+        })
+    }
+}
+
+/// Linear interpolation resampler.
+pub fn resample(data: &[f32], from: f64, to: f64) -> Vec<f32> {
+    if data.is_empty() || from <= 0.0 || to <= 0.0 || from == to {
+        return data.to_vec();
+    }
+
+    let step = from / to;
+    let len = ((data.len() as f64) / step).floor() as usize;
+
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let idx = pos.floor() as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = data[idx];
+            let b = data.get(idx + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Plays a sample pitched from `root_hz` to `freq` each time the
+/// input envelope rises above `threshold`.
+#[derive(Clone)]
+pub struct SamplePlayer {
+    sample: Sample,
+    pitch: f64,
+    threshold: f32,
+    sample_rate: f64,
+    step: f64,
+    position: Option<f64>,
+    envelope: f32,
+    armed: bool,
+}
+
+const ENVELOPE_RELEASE: f32 = 0.999;
+
+impl SamplePlayer {
+    pub fn new(sample: Sample, freq: f32, root_hz: f32, threshold: f32) -> Self {
+        let pitch = if root_hz > 0.0 {
+            (freq / root_hz) as f64
+        } else {
+            1.0
+        };
+        let mut player = Self {
+            sample,
+            pitch,
+            threshold,
+            sample_rate: SAMPLE_RATE,
+            step: pitch,
+            position: None,
+            envelope: 0.0,
+            armed: true,
+        };
+        player.set_sample_rate(SAMPLE_RATE);
+        player
+    }
+}
+
+impl AudioNode for SamplePlayer {
+    const ID: u64 = 0x7265_6473_6972_0001;
+    type Sample = f32;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.position = None;
+        self.envelope = 0.0;
+        self.armed = true;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.step = self.pitch * self.sample.sample_rate / sample_rate;
+    }
+
+    #[inline]
+    fn tick(&mut self, input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        let level = input[0].abs();
+        self.envelope = level.max(self.envelope * ENVELOPE_RELEASE);
+
+        if self.armed && self.envelope > self.threshold {
+            self.position = Some(0.0);
+            self.armed = false;
+        } else if self.envelope < self.threshold * 0.5 {
+            self.armed = true;
+        }
+
+        let data = self.sample.data.as_slice();
+        let value = match self.position {
+            Some(pos) if (pos as usize) < data.len() => {
+                let idx = pos as usize;
+                let frac = (pos - idx as f64) as f32;
+                let a = data[idx];
+                let b = data.get(idx + 1).copied().unwrap_or(0.0);
+                self.position = Some(pos + self.step);
+                a + (b - a) * frac
+            }
+            _ => {
+                self.position = None;
+                0.0
+            }
+        };
+
+        [value].into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resamples_to_target_rate() {
+        let data = (0..480).map(|i| i as f32).collect::<Vec<_>>();
+
+        let up = resample(data.as_slice(), 48000.0, 96000.0);
+        assert_eq!(up.len(), 960);
+        assert_eq!(up[1], 0.5);
+
+        let down = resample(data.as_slice(), 48000.0, 24000.0);
+        assert_eq!(down.len(), 240);
+        assert_eq!(down[1], 2.0);
+
+        assert_eq!(resample(data.as_slice(), 44100.0, 44100.0), data);
+    }
+
+    #[test]
+    fn player_triggers_above_threshold() {
+        let sample = Sample {
+            data: Arc::new(vec![1.0; 64]),
+            sample_rate: SAMPLE_RATE,
+        };
+        let mut player = An(SamplePlayer::new(sample, 440.0, 440.0, 0.5));
+
+        assert_eq!(player.filter_mono(0.1), 0.0);
+        assert_eq!(player.filter_mono(0.9), 1.0);
+
+        let played = (0..128).map(|_| player.filter_mono(0.0)).collect::<Vec<_>>();
+        assert!(played[..62].iter().all(|v| *v > 0.0));
+        assert!(played[64..].iter().all(|v| *v == 0.0));
+    }
+}
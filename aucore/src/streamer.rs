@@ -8,7 +8,7 @@ use futures::task::SpawnExt;
 use futures::StreamExt;
 
 use lazy_static::lazy_static;
-use app_core::play::{PlayOperation, PlayOperationOutput};
+use app_core::play::{CaptureOutput, PlayOperation, PlayOperationOutput};
 
 pub use futures::channel::mpsc::UnboundedReceiver;
 
@@ -33,6 +33,7 @@ struct CoreStreamer {
     pub op_receiver: Arc<Mutex<Receiver<PlayOperation>>>,
     pub op_sender: Arc<Mutex<Sender<PlayOperation>>>,
     pub resolve_sender: Arc<Mutex<UnboundedSender<PlayOperationOutput>>>,
+    pub capture_sender: Arc<Mutex<Option<UnboundedSender<CaptureOutput>>>>,
     pub render_sender: Arc<Mutex<Sender<ViewModel>>>,
     pub render_receiver: Arc<Mutex<Receiver<ViewModel>>>,
     pub input_sender: Arc<Mutex<Sender<Vec<Vec<f32>>>>>,
@@ -52,6 +53,7 @@ impl CoreStreamer {
                 render_sender: Arc::new(Mutex::new(render_sender)),
                 render_receiver: Arc::new(Mutex::new(render_receiver)),
                 resolve_sender: Arc::new(Mutex::new(resolve_sender)),
+                capture_sender: Arc::new(Mutex::new(None)),
                 op_sender: Arc::new(Mutex::new(op_sender)),
                 op_receiver: Arc::new(Mutex::new(op_receiver)),
                 input_sender: Arc::new(Mutex::new(input_sender)),
@@ -73,6 +75,11 @@ impl CoreStreamer {
 
         op_sender.send(event).expect("send op");
     }
+
+    fn capture(&self, capture_sender: UnboundedSender<CaptureOutput>) {
+        let mut capture = self.capture_sender.lock().expect("lock capture");
+        *capture = Some(capture_sender);
+    }
 }
 
 cfg_if::cfg_if! {
@@ -115,6 +122,7 @@ impl AUCoreBridge {
         let CoreStreamer {
             op_receiver,
             resolve_sender,
+            capture_sender,
             render_sender,
             input_receiver,
             ..
@@ -129,6 +137,7 @@ impl AUCoreBridge {
                 let render_sender = render_sender.lock().expect("render lock");
                 let op_receiver = op_receiver.lock().expect("op receiver lock");
                 let resolve_sender = resolve_sender.lock().expect("resolve sender lock");
+                let mut capture_sender = capture_sender.lock().expect("capture sender lock");
                 let mut ops = vec![PlayOperation::Input(input)];
 
                 match op_receiver.try_recv() {
@@ -153,8 +162,13 @@ impl AUCoreBridge {
                             Effect::Resolve(op) => resolve_sender
                                 .unbounded_send(op.operation)
                                 .expect("send resolve"),
-                            Effect::Capture(d) => {
-                                todo!()
+                            Effect::Capture(req) => {
+                                if let Some(sender) = capture_sender.as_ref() {
+                                    if sender.unbounded_send(req.operation).is_err() {
+                                        log::debug!("capture receiver is gone");
+                                        *capture_sender = None;
+                                    }
+                                }
                             }
                         }
                     }
@@ -239,4 +253,30 @@ impl AUCoreBridge {
 
         rx
     }
+
+    /// Streams the captures of the processor, replacing the previous receiver.
+    pub fn capture(&self) -> UnboundedReceiver<Vec<u8>> {
+        let (s_capture, mut r_capture) = unbounded::<CaptureOutput>();
+
+        self.core.lock().expect("lock core").capture(s_capture);
+
+        let (sx, rx) = unbounded();
+
+        let cx_future = async move {
+            while let Some(d) = r_capture.next().await {
+                log::trace!("send capture output");
+                if sx
+                    .unbounded_send(bincode::serialize(&d).expect("serialize capture"))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            log::debug!("capture receive complete");
+        };
+
+        self.pool.spawn(cx_future).expect("spawn capture convert");
+
+        rx
+    }
 }
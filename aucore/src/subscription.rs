@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use app_core::play::Stream;
+
+/// Counts processed frames per subscribed stream and tells
+/// which streams are due for a push.
+pub struct Subscriptions {
+    sample_rate: f64,
+    streams: HashMap<Stream, (f64, f64)>,
+}
+
+impl Subscriptions {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn subscribe(&mut self, stream: Stream, rate_hz: f32) -> bool {
+        if rate_hz > 0.0 && rate_hz.is_finite() {
+            let period = self.sample_rate / rate_hz as f64;
+            self.streams.insert(stream, (period, 0.0));
+            true
+        } else {
+            self.streams.remove(&stream);
+            false
+        }
+    }
+
+    pub fn unsubscribe(&mut self, stream: Stream) {
+        self.streams.remove(&stream);
+    }
+
+    pub fn is_subscribed(&self, stream: Stream) -> bool {
+        self.streams.contains_key(&stream)
+    }
+
+    /// Advances all clocks by `frames`, returns the streams to push.
+    pub fn tick(&mut self, frames: usize) -> Vec<Stream> {
+        self.streams
+            .iter_mut()
+            .filter_map(|(stream, (period, clock))| {
+                *clock += frames as f64;
+                if *clock >= *period {
+                    *clock %= *period;
+                    Some(*stream)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pushes_each_stream_at_its_rate() {
+        let mut subs = Subscriptions::new(48000.0);
+        assert!(subs.subscribe(Stream::String, 100.0));
+        assert!(subs.subscribe(Stream::NodeLevels, 25.0));
+        assert!(!subs.subscribe(Stream::Spectrum, 0.0));
+
+        let mut string = 0;
+        let mut levels = 0;
+        for _ in 0..(48000 / 128) {
+            for stream in subs.tick(128) {
+                match stream {
+                    Stream::String => string += 1,
+                    Stream::NodeLevels => levels += 1,
+                    s => panic!("unexpected {s:?}"),
+                }
+            }
+        }
+        assert!((99..=100).contains(&string), "{string}");
+        assert!((24..=25).contains(&levels), "{levels}");
+
+        subs.unsubscribe(Stream::String);
+        assert!(!subs.is_subscribed(Stream::String));
+        assert!(subs.tick(48000).iter().all(|s| *s == Stream::NodeLevels));
+    }
+}
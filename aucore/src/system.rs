@@ -1,13 +1,22 @@
 use app_core::{
-    instrument::{Config, Node},
+    instrument::{node::node_gains, Config, Node},
+    play::{Effects, NodeLevel, NodeMetering},
     tuner::TuningValue,
 };
 use fundsp::hacker32::*;
 
+use crate::{
+    effects::Sends,
+    gain,
+    levels::NodeMeter,
+    sample::SampleBank,
+    voice::{chain, voice_model},
+    window,
+};
+
 pub const SAMPLE_RATE: f64 = 44100.0;
-const SNOOP_SIZE: usize = 64;
 const CHANNELS: usize = 2;
-pub const MUL: f32 = 100000.0;
+const GATE_SMOOTHING: f32 = 0.01;
 
 pub struct System {
     pub net_be: BigBlockAdapter32,
@@ -15,15 +24,24 @@ pub struct System {
     pub channels: usize,
     pub sample_rate: f64,
     pub nodes: Vec<NodeId>,
-    pub node_snp: Vec<(Snoop<f32>, usize)>,
+    pub node_meters: Vec<NodeMeter>,
     pub b_centres: Vec<Shared<f32>>,
     pub b_qs: Vec<Shared<f32>>,
     pub n_fs: Vec<Shared<f32>>,
+    pub n_gains: Vec<(usize, Shared<f32>)>,
     pub out_snp: Snoop<f32>,
+    pub sends: Sends,
 }
 
 impl System {
-    pub fn new(nodes_data: &[Node], config: &Config, tuning: &[TuningValue]) -> Self {
+    pub fn new(
+        nodes_data: &[Node],
+        config: &Config,
+        tuning: &[TuningValue],
+        samples: &SampleBank,
+        effects: &Effects,
+        metering: &NodeMetering,
+    ) -> Self {
         let sample_rate = SAMPLE_RATE;
         let channels = Ord::min(config.groups, CHANNELS);
         let mut net = Net32::new(1, channels);
@@ -31,31 +49,25 @@ impl System {
 
         let size = nodes_data.len();
         let mut nodes = vec![];
-        let mut node_snp = vec![];
+        let mut node_meters = vec![];
         let mut b_centres = vec![];
         let mut b_qs = vec![];
         let mut n_fs = vec![];
+        let mut n_gains = vec![];
 
         let mut input_subnet = Net32::new(1, size);
         let mut output_subnet = Net32::new(size, channels);
 
-        let input_pipe = declick_s(0.75);
-
-        let input_pipe_id = match nodes_data.len() {
-            2 => input_subnet.push(Box::new(input_pipe >> split::<U2>())),
-            3 => input_subnet.push(Box::new(input_pipe >> split::<U3>())),
-            4 => input_subnet.push(Box::new(input_pipe >> split::<U4>())),
-            5 => input_subnet.push(Box::new(input_pipe >> split::<U5>())),
-            6 => input_subnet.push(Box::new(input_pipe >> split::<U6>())),
-            9 => input_subnet.push(Box::new(input_pipe >> split::<U9>())),
-            10 => input_subnet.push(Box::new(input_pipe >> split::<U10>())),
-            12 => input_subnet.push(Box::new(input_pipe >> split::<U12>())),
-            n => todo!("support for {n} nodes"),
-        };
+        let input_pipe_id = input_subnet.push(Box::new(declick_s(0.75)));
 
         input_subnet.connect_input(0, input_pipe_id, 0);
 
-        for (i, (node_data, tuning)) in nodes_data.iter().zip(tuning).enumerate() {
+        for (i, ((node_data, tuning), (f_n, gate))) in nodes_data
+            .iter()
+            .zip(tuning)
+            .zip(node_gains(nodes_data))
+            .enumerate()
+        {
             assert_eq!(tuning.0, node_data.f_n, "pair f_n");
 
             let bp_f = shared(tuning.1);
@@ -69,27 +81,37 @@ impl System {
 
             // todo: use hid input
             let bp_q = shared(1.0 / size as f32);
-            let ch_mul = 1.0 + MUL - MUL * tuning.2;
-
-            log::info!("amp channel input by {ch_mul}");
-            let (n_snp, snp_an) = snoop(SNOOP_SIZE);
-            node_snp.push((n_snp, node_data.f_n));
-            let bp_n = mul(ch_mul)
-                >> (pass() | var(&bp_f) | var(&bp_q))
-                >> bandrez()
-                >> pluck(node_data.freq.1, 0.75, 0.25);
-                
+            let ch_mul = gain::sensitivity(tuning.2);
+
+            log::info!("amp channel input by {} dB", gain::sensitivity_db(tuning.2));
+            let voice = voice_model(&node_data.voice, samples);
+            let bp_n = chain(vec![
+                Box::new(mul(ch_mul) >> (pass() | var(&bp_f) | var(&bp_q)) >> bandrez()),
+                voice.exciter(node_data),
+            ]);
+
             b_centres.push(bp_f);
             b_qs.push(bp_q);
 
             let bp_id = input_subnet.push(Box::new(bp_n));
 
-            input_subnet.connect(input_pipe_id, i, bp_id, 0);
+            input_subnet.connect(input_pipe_id, 0, bp_id, 0);
             input_subnet.connect_output(bp_id, 0, i);
 
             let n_f = shared(node_data.freq.0);
-            let mut node = (var(&n_f) | pass()) >> (sine() * follow(0.075)) >> bell_hz(node_data.freq.1, 0.25, 1.75) >> snp_an;
+            let n_gain = shared(gate);
+            let meter = NodeMeter::new(node_data.f_n);
+            let mut node = chain(vec![
+                voice.body(node_data, &n_f),
+                Box::new(
+                    gain::protect()
+                        >> (pass() * (var(&n_gain) >> follow(GATE_SMOOTHING)))
+                        >> meter.node(metering),
+                ),
+            ]);
+            node_meters.push(meter);
             n_fs.push(n_f);
+            n_gains.push((f_n, n_gain));
 
             log::debug!("created node: {}", node.display());
 
@@ -100,7 +122,7 @@ impl System {
             nodes.push(node_id);
         }
 
-        let (out_snp, an_snp) = snoop(SNOOP_SIZE);
+        let (out_snp, an_snp) = snoop(window::capacity(config));
 
         let output_pipe_id = match channels {
             1 => {
@@ -108,50 +130,38 @@ impl System {
                     .last()
                     .map(|n| (n.freq.1 * 5.0, n.freq.1 - n.freq.0))
                     .unwrap();
-                let r = resonator_hz(r_f, d_f) >> an_snp >> mul(10.0) >> pinkpass();
-
-                match nodes_data.len() {
-                    2 => output_subnet.push(Box::new(join::<U2>() >> r)),
-                    3 => output_subnet.push(Box::new(join::<U3>() >> r)),
-                    5 => output_subnet.push(Box::new(join::<U5>() >> r)),
-                    n => todo!("support {n} nodes, 1 channel"),
-                }
+                let r = resonator_hz(r_f, d_f)
+                    >> an_snp
+                    >> mul(10.0)
+                    >> pinkpass()
+                    >> gain::protect();
+
+                output_subnet.push(Box::new(r))
             }
             2 => {
                 let (lr_f, ld_f) = nodes_data
                     .iter()
                     .filter(|n| n.pan < 0)
                     .last()
+                    .or(nodes_data.last())
                     .map(|n| (n.freq.1 * 2.0, n.freq.1 - n.freq.0))
                     .unwrap();
                 let (rr_f, rd_f) = nodes_data
                     .iter()
                     .filter(|n| n.pan > 0)
                     .last()
+                    .or(nodes_data.last())
                     .map(|n| (n.freq.1 * 4.0, n.freq.1 - n.freq.0))
                     .unwrap();
                 let r = (resonator_hz(lr_f, ld_f) | resonator_hz(rr_f, rd_f))
                     >> (split::<U2>() | split::<U2>())
                     >> (pass() | join::<U2>() | pass())
                     >> (pass() | an_snp | pass())
-                    >> (pinkpass() | sink() | pinkpass());
-
-                match nodes_data.len() {
-                    4 => output_subnet.push(Box::new((join::<U2>() | join::<U2>()) >> r)),
-                    6 => output_subnet.push(Box::new((join::<U3>() | join::<U3>()) >> r)),
-                    9 => {
-                        let right = nodes_data.iter().filter(|n| n.pan > 0).count();
-                        let left = nodes_data.iter().filter(|n| n.pan < 0).count();
-                        if right > left {
-                            output_subnet.push(Box::new((join::<U3>() | join::<U6>()) >> r))
-                        } else {
-                            output_subnet.push(Box::new((join::<U6>() | join::<U3>()) >> r))
-                        }
-                    }
-                    10 => output_subnet.push(Box::new((join::<U5>() | join::<U5>()) >> r)),
-                    12 => output_subnet.push(Box::new((join::<U6>() | join::<U6>()) >> r)),
-                    n => todo!("support {n} nodes, 2 channels"),
-                }
+                    >> ((pinkpass() >> gain::protect())
+                        | sink()
+                        | (pinkpass() >> gain::protect()));
+
+                output_subnet.push(Box::new(r))
             }
             n => todo!("support {n} channels"),
         };
@@ -167,11 +177,15 @@ impl System {
             n => todo!("support {n} channels"),
         }
 
-        for (node_id, node_data) in nodes.iter().zip(nodes_data) {
-            if node_data.pan > 0 && channels > 1 {
-                output_subnet.connect(*node_id, 0, output_pipe_id, 1);
-            } else {
-                output_subnet.connect(*node_id, 0, output_pipe_id, 0);
+        for ch in 0..channels {
+            let inputs = nodes
+                .iter()
+                .zip(nodes_data)
+                .filter(|(_, n)| usize::from(n.pan > 0 && channels > 1) == ch)
+                .map(|(node_id, _)| *node_id)
+                .collect::<Vec<_>>();
+            if let Some(sum_id) = mix(&mut output_subnet, &inputs) {
+                output_subnet.connect(sum_id, 0, output_pipe_id, ch);
             }
         }
 
@@ -181,10 +195,20 @@ impl System {
         let in_id = net.push(Box::new(input_subnet));
         let out_id = net.push(Box::new(output_subnet));
 
+        let sends = Sends::new(effects);
+        let fx_id = match channels {
+            1 => net.push(Box::new(
+                split::<U2>() >> sends.stereo() >> join::<U2>() >> gain::protect(),
+            )),
+            2 => net.push(Box::new(sends.stereo() >> (gain::protect() | gain::protect()))),
+            n => unreachable!("{n} channels, the system has 1 to {CHANNELS}"),
+        };
+
         net.connect_input(0, in_id, 0);
 
         for ch in 0..channels {
-            net.connect_output(out_id, ch, ch);
+            net.connect(out_id, ch, fx_id, ch);
+            net.connect_output(fx_id, ch, ch);
         }
 
         for i in 0..size {
@@ -206,9 +230,231 @@ impl System {
             b_centres,
             b_qs,
             n_fs,
+            n_gains,
             nodes,
             out_snp,
-            node_snp
+            node_meters,
+            sends,
+        }
+    }
+
+    pub fn set_node_gains(&self, gains: &[(usize, f32)]) {
+        for (f_n, gate) in gains {
+            match self.n_gains.iter().find(|(n, _)| n == f_n) {
+                Some((_, gain)) => gain.set_value(*gate),
+                None => log::warn!("no node {f_n} in system"),
+            }
+        }
+    }
+
+    pub fn node_levels(&self) -> Vec<NodeLevel> {
+        self.node_meters.iter().map(|m| m.level()).collect()
+    }
+}
+
+/// Sums the outputs of `inputs` with a chain of adders, `None` without inputs.
+fn mix(net: &mut Net32, inputs: &[NodeId]) -> Option<NodeId> {
+    let (first, rest) = inputs.split_first()?;
+
+    Some(rest.iter().fold(*first, |sum_id, input_id| {
+        let adder_id = net.push(Box::new(pass() + pass()));
+        net.connect(sum_id, 0, adder_id, 0);
+        net.connect(*input_id, 0, adder_id, 1);
+        adder_id
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use app_core::{
+        geometry::Rect,
+        instrument::{keyboard::Keyboard, node::spawn_all_nodes, Handedness, Voice},
+    };
+    use std::sync::Arc;
+
+    use hecs::World;
+
+    use super::*;
+    use crate::sample::Sample;
+
+    const BLOCK: usize = 256;
+
+    fn nodes_for(config: &Config) -> Vec<Node> {
+        let mut world = World::new();
+        Keyboard::spawn(&mut world, config);
+        spawn_all_nodes(&mut world, Voice::default())
+            .into_iter()
+            .map(|e| *world.get::<&Node>(e).expect("node"))
+            .collect()
+    }
+
+    fn assert_output_bounded(config: &Config, amp: f32, samples: &SampleBank) {
+        let nodes = nodes_for(config);
+        let tuning = nodes
+            .iter()
+            .map(|n| (n.f_n, n.freq.0, amp))
+            .collect::<Vec<TuningValue>>();
+        let mut sys = System::new(
+            nodes.as_slice(),
+            config,
+            tuning.as_slice(),
+            samples,
+            &Effects {
+                reverb_wet: 1.0,
+                delay_wet: 1.0,
+                delay_feedback: 0.9,
+                chorus_wet: 1.0,
+                ..Default::default()
+            },
+            &Default::default(),
+        );
+
+        let mut output = vec![vec![0_f32; BLOCK]; sys.channels];
+        for block in 0..(SAMPLE_RATE as usize / BLOCK) {
+            let input = (0..BLOCK)
+                .map(|i| {
+                    let t = (block * BLOCK + i) as f32 / SAMPLE_RATE as f32;
+                    nodes
+                        .iter()
+                        .map(|n| (t * n.freq.0 * std::f32::consts::TAU).sin())
+                        .sum::<f32>()
+                })
+                .collect::<Vec<_>>();
+
+            let mut out = output
+                .iter_mut()
+                .map(|ch| ch.as_mut_slice())
+                .collect::<Vec<_>>();
+            sys.net_be
+                .process(BLOCK, &[input.as_slice()], out.as_mut_slice());
+
+            for s in output.iter().flatten() {
+                assert!(s.is_finite(), "amp {amp}: non finite sample");
+                assert!((-1.0..=1.0).contains(s), "amp {amp}: sample {s} clipped");
+            }
+        }
+    }
+
+    #[test]
+    fn muted_nodes_are_silent() {
+        let config = Config::new(430.0, 932.0, 476.0, Default::default());
+        let mut nodes = nodes_for(&config);
+        nodes[0].muted = true;
+        nodes[1].solo = true;
+        let tuning = nodes
+            .iter()
+            .map(|n| (n.f_n, n.freq.0, 0.5))
+            .collect::<Vec<TuningValue>>();
+        let mut sys = System::new(
+            nodes.as_slice(),
+            &config,
+            tuning.as_slice(),
+            &Default::default(),
+            &Default::default(),
+            &Default::default(),
+        );
+        sys.set_node_gains(&[(usize::MAX, 1.0)]);
+
+        let mut output = vec![vec![0_f32; BLOCK]; sys.channels];
+        for block in 0..(SAMPLE_RATE as usize / BLOCK) {
+            let input = (0..BLOCK)
+                .map(|i| {
+                    let t = (block * BLOCK + i) as f32 / SAMPLE_RATE as f32;
+                    (t * nodes[0].freq.0 * std::f32::consts::TAU).sin()
+                })
+                .collect::<Vec<_>>();
+            let mut out = output
+                .iter_mut()
+                .map(|ch| ch.as_mut_slice())
+                .collect::<Vec<_>>();
+            sys.net_be
+                .process(BLOCK, &[input.as_slice()], out.as_mut_slice());
+        }
+
+        for level in sys.node_levels() {
+            if level.f_n != nodes[1].f_n {
+                assert_eq!(level.peak, 0.0, "f_n {} not gated", level.f_n);
+            }
+        }
+    }
+
+    #[test]
+    fn output_within_unit_range_for_any_tuned_amplitude() {
+        let config = Config::new(430.0, 932.0, 476.0, Default::default());
+
+        for step in 0..=10 {
+            assert_output_bounded(&config, step as f32 / 10.0, &Default::default());
+        }
+    }
+
+    #[test]
+    fn output_within_unit_range_for_every_voice() {
+        let samples = SampleBank::from([(
+            0,
+            Sample {
+                data: Arc::new((0..4410).map(|i| (i as f32 * 0.1).sin()).collect()),
+                sample_rate: SAMPLE_RATE,
+            },
+        )]);
+
+        for voice in [
+            Voice::SineBell,
+            Voice::PluckedString,
+            Voice::Fm {
+                ratio: 1.5,
+                index: 2.0,
+            },
+            Voice::NoiseBand { q: 4.0 },
+            Voice::Sample {
+                slot: 0,
+                root_hz: 440.0,
+                threshold: 0.1,
+            },
+        ] {
+            let config = Config {
+                voice,
+                ..Config::new(430.0, 932.0, 476.0, Default::default())
+            };
+            assert_output_bounded(&config, 0.0, &samples);
+            assert_output_bounded(&config, 1.0, &samples);
+        }
+    }
+
+    #[test]
+    fn builds_for_every_layout() {
+        let along = Rect::new(0.0, 2208.0, 874.0, 894.0);
+        let across = Rect::new(1094.0, 1114.0, 0.0, 1768.0);
+        let configs = [
+            Config::new(430.0, 932.0, 476.0, Default::default()),
+            Config::new(932.0, 430.0, 476.0, Default::default()),
+            Config::new(1920.0, 1080.0, 96.0, [50.0, 20.0, 10.0, 25.0]),
+            Config::new(1080.0, 1152.0, 420.0, Default::default()),
+            Config::new(3840.0, 1080.0, 110.0, Default::default()),
+            Config::new(3840.0, 1080.0, 110.0, [0.0, 300.0, 0.0, 300.0]),
+            Config::new_with_hinge(2208.0, 1768.0, 373.0, Default::default(), Some(along)),
+            Config::new_with_hinge(2208.0, 1768.0, 373.0, Default::default(), Some(across)),
+        ];
+
+        for config in configs
+            .into_iter()
+            .flat_map(|config| [config.clone(), config.with_handedness(Handedness::Left)])
+        {
+            let nodes = nodes_for(&config);
+            let tuning = nodes
+                .iter()
+                .map(|n| (n.f_n, n.freq.0, 0.5))
+                .collect::<Vec<TuningValue>>();
+            let sys = System::new(
+                nodes.as_slice(),
+                &config,
+                tuning.as_slice(),
+                &Default::default(),
+                &Default::default(),
+                &Default::default(),
+            );
+
+            assert_eq!(sys.size, config.n_buttons, "{config:?}");
+            assert_eq!(sys.channels, config.groups.min(CHANNELS), "{config:?}");
         }
     }
 }
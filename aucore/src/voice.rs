@@ -0,0 +1,145 @@
+use app_core::instrument::{Node, Voice};
+use fundsp::hacker32::*;
+
+use crate::sample::{Sample, SampleBank, SamplePlayer};
+
+/// Builds the two halves of a chime node.
+///
+/// The exciter sits on the input side after the band-pass resonator,
+/// the body turns the excitation into sound at the node frequency.
+pub trait VoiceModel {
+    fn exciter(&self, node: &Node) -> Box<dyn AudioUnit32>;
+    fn body(&self, node: &Node, freq: &Shared<f32>) -> Box<dyn AudioUnit32>;
+}
+
+pub struct SineBell;
+
+impl VoiceModel for SineBell {
+    fn exciter(&self, node: &Node) -> Box<dyn AudioUnit32> {
+        Box::new(pluck(node.freq.1, 0.75, 0.25))
+    }
+
+    fn body(&self, node: &Node, freq: &Shared<f32>) -> Box<dyn AudioUnit32> {
+        Box::new(
+            (var(freq) | pass())
+                >> (sine() * follow(0.075))
+                >> bell_hz(node.freq.1, 0.25, 1.75),
+        )
+    }
+}
+
+pub struct PluckedString;
+
+impl VoiceModel for PluckedString {
+    fn exciter(&self, node: &Node) -> Box<dyn AudioUnit32> {
+        Box::new(lowpass_hz(node.freq.1, 0.5))
+    }
+
+    fn body(&self, node: &Node, _freq: &Shared<f32>) -> Box<dyn AudioUnit32> {
+        Box::new(pluck(node.freq.0, 0.8, 0.3) >> dcblock())
+    }
+}
+
+pub struct Fm {
+    pub ratio: f32,
+    pub index: f32,
+}
+
+impl VoiceModel for Fm {
+    fn exciter(&self, node: &Node) -> Box<dyn AudioUnit32> {
+        Box::new(pluck(node.freq.1, 0.75, 0.25))
+    }
+
+    fn body(&self, _node: &Node, freq: &Shared<f32>) -> Box<dyn AudioUnit32> {
+        let modulator = (var(freq) * self.ratio) >> sine();
+        let deviation = var(freq) * (self.ratio * self.index);
+        let carrier = (var(freq) + modulator * deviation) >> sine();
+
+        Box::new(carrier * follow(0.075))
+    }
+}
+
+pub struct NoiseBand {
+    pub q: f32,
+}
+
+impl VoiceModel for NoiseBand {
+    fn exciter(&self, _node: &Node) -> Box<dyn AudioUnit32> {
+        Box::new(follow(0.01))
+    }
+
+    fn body(&self, node: &Node, _freq: &Shared<f32>) -> Box<dyn AudioUnit32> {
+        Box::new((noise() * follow(0.075)) >> bandpass_hz(node.freq.0, self.q.max(0.1)))
+    }
+}
+
+pub struct Sampled {
+    pub sample: Sample,
+    pub root_hz: f32,
+    pub threshold: f32,
+}
+
+impl VoiceModel for Sampled {
+    fn exciter(&self, _node: &Node) -> Box<dyn AudioUnit32> {
+        Box::new(pass())
+    }
+
+    fn body(&self, node: &Node, _freq: &Shared<f32>) -> Box<dyn AudioUnit32> {
+        Box::new(An(SamplePlayer::new(
+            self.sample.clone(),
+            node.freq.0,
+            self.root_hz,
+            self.threshold,
+        )))
+    }
+}
+
+pub fn voice_model(voice: &Voice, samples: &SampleBank) -> Box<dyn VoiceModel> {
+    match *voice {
+        Voice::SineBell => Box::new(SineBell),
+        Voice::PluckedString => Box::new(PluckedString),
+        Voice::Fm { ratio, index } => Box::new(Fm { ratio, index }),
+        Voice::NoiseBand { q } => Box::new(NoiseBand { q }),
+        Voice::Sample {
+            slot,
+            root_hz,
+            threshold,
+        } => match samples.get(&slot) {
+            Some(sample) => Box::new(Sampled {
+                sample: sample.clone(),
+                root_hz,
+                threshold,
+            }),
+            None => {
+                log::warn!("no sample loaded in slot {slot}, using sine bell");
+                Box::new(SineBell)
+            }
+        },
+    }
+}
+
+/// Single input, single output network running `units` in series.
+pub fn chain(units: Vec<Box<dyn AudioUnit32>>) -> Net32 {
+    let mut net = Net32::new(1, 1);
+    let ids = units
+        .into_iter()
+        .map(|unit| net.push(unit))
+        .collect::<Vec<_>>();
+
+    match (ids.first(), ids.last()) {
+        (Some(first), Some(last)) => {
+            net.connect_input(0, *first, 0);
+            for pair in ids.windows(2) {
+                net.connect(pair[0], 0, pair[1], 0);
+            }
+            net.connect_output(*last, 0, 0);
+        }
+        _ => {
+            let id = net.push(Box::new(pass()));
+            net.connect_input(0, id, 0);
+            net.connect_output(id, 0, 0);
+        }
+    }
+
+    net
+}
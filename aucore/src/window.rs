@@ -0,0 +1,73 @@
+use app_core::instrument::{Config, Decimation};
+
+/// Output samples reduced to a single min/max pair.
+const MIN_MAX_BUCKET: usize = 16;
+
+/// Number of output samples kept for the outbound string.
+pub fn capacity(config: &Config) -> usize {
+    let size = config.snoop_size.max(2);
+    match config.snoop_decimation {
+        Decimation::Off => size,
+        Decimation::MinMax => size / 2 * MIN_MAX_BUCKET,
+    }
+}
+
+/// Reduces the snooped samples to `config.snoop_size` points.
+///
+/// Min/max keeps both extremes of each bucket in the order they occurred,
+/// so short transients still reach the string.
+pub fn decimate(data: &[f32], config: &Config) -> Vec<f32> {
+    match config.snoop_decimation {
+        Decimation::Off => data.iter().take(config.snoop_size).copied().collect(),
+        Decimation::MinMax => data
+            .chunks(MIN_MAX_BUCKET)
+            .flat_map(|bucket| {
+                let (min_i, min) = extreme(bucket, |a, b| a < b);
+                let (max_i, max) = extreme(bucket, |a, b| a > b);
+                if min_i <= max_i {
+                    [min, max]
+                } else {
+                    [max, min]
+                }
+            })
+            .take(config.snoop_size)
+            .collect(),
+    }
+}
+
+fn extreme(bucket: &[f32], better: impl Fn(f32, f32) -> bool) -> (usize, f32) {
+    bucket
+        .iter()
+        .copied()
+        .enumerate()
+        .fold((0, bucket[0]), |acc, (i, v)| if better(v, acc.1) { (i, v) } else { acc })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_max_keeps_transients() {
+        let config = Config {
+            snoop_size: 8,
+            snoop_decimation: Decimation::MinMax,
+            ..Default::default()
+        };
+        let mut data = vec![0.0; capacity(&config)];
+        data[20] = 0.9;
+        data[21] = -0.7;
+
+        let points = decimate(data.as_slice(), &config);
+        assert_eq!(points.len(), 8);
+        assert_eq!(points[2..4], [0.9, -0.7]);
+        assert!(points.iter().filter(|p| **p != 0.0).count() == 2);
+
+        let config = Config {
+            snoop_decimation: Decimation::Off,
+            ..config
+        };
+        assert_eq!(capacity(&config), 8);
+        assert_eq!(decimate(data.as_slice(), &config), vec![0.0; 8]);
+    }
+}
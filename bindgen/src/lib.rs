@@ -63,6 +63,10 @@ impl AUReceiver {
         AUReceiver(Mutex::new(au.0.request(bytes)))
     }
 
+    pub fn au_capture(au: &AUCoreBridge) -> AUReceiver {
+        AUReceiver(Mutex::new(au.0.capture()))
+    }
+
     async fn au_receive(&self) -> Option<Vec<u8>> {
         log::trace!("wait for receiver lock");
         let mut rx = self.0.lock().await;
@@ -78,6 +82,12 @@ pub fn au_request(arc_self: Arc<AUCoreBridge>, bytes: Vec<u8>) -> Arc<AUReceiver
     Arc::new(AUReceiver::au_request(au.as_ref(), bytes))
 }
 
+#[uniffi::export]
+pub fn au_capture(arc_self: Arc<AUCoreBridge>) -> Arc<AUReceiver> {
+    let au = arc_self.clone();
+    Arc::new(AUReceiver::au_capture(au.as_ref()))
+}
+
 #[uniffi::export]
 pub async fn au_receive(arc_self: Arc<AUReceiver>) -> Option<Vec<u8>> {
     arc_self.au_receive().await
@@ -5,16 +5,33 @@ import OSLog
 @MainActor
 class AnimationClock: NSObject, ObservableObject {
     @Published var ts: Double = 0
-    var onTick: (Double?) -> Void
+    var onTicks: [UInt64: (Double?) -> Void] = [:]
     var onStart: (Double) -> Void
     
     var link: CADisplayLink?
     
-    init(onTick: @escaping (Double?) -> Void = {_ in}, onStart: @escaping (Double) -> Void = {_ in}) {
-        self.onTick = onTick
+    init(onStart: @escaping (Double) -> Void = {_ in}) {
         self.onStart = onStart
     }
     
+    func start(_ id: UInt64, onTick: @escaping (Double?) -> Void) {
+        if let running = self.onTicks.updateValue(onTick, forKey: id) {
+            running(nil)
+        }
+        if self.link == nil {
+            self.createDisplayLink()
+        }
+    }
+    
+    func stop(_ id: UInt64) {
+        if let onTick = self.onTicks.removeValue(forKey: id) {
+            onTick(nil)
+        }
+        if self.onTicks.isEmpty {
+            self.deleteDisplayLink()
+        }
+    }
+    
     func createDisplayLink() {
         if (self.link != nil) {
             self.deleteDisplayLink()
@@ -30,7 +47,10 @@ class AnimationClock: NSObject, ObservableObject {
     func deleteDisplayLink() {
         if let link = self.link {
             link.invalidate()
-            self.onTick(nil)
+            for onTick in self.onTicks.values {
+                onTick(nil)
+            }
+            self.onTicks.removeAll()
         }
         else {
             Logger().log("deleteDisplayLink called with no link")
@@ -38,6 +58,10 @@ class AnimationClock: NSObject, ObservableObject {
         self.link = nil
     }
     
+    func setPaused(_ paused: Bool) {
+        self.link?.isPaused = paused
+    }
+    
     @objc func step(displaylink: CADisplayLink) {
         let old_ts = self.ts
         self.ts = displaylink.targetTimestamp * 1000
@@ -46,7 +70,9 @@ class AnimationClock: NSObject, ObservableObject {
             self.onStart(self.ts)
         }
         else {
-            self.onTick(self.ts)
+            for onTick in self.onTicks.values {
+                onTick(self.ts)
+            }
         }
     }
 }
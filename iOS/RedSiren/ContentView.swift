@@ -79,18 +79,21 @@ struct ContentView: View {
             )
         }.onAppear{
             Logger().log("set cbs");
-            self.core.startClock = { cb in
-                self.clock.onTick = cb
-                self.clock.createDisplayLink()
-                Logger().log("starting");
+            self.core.startClock = { id, cb in
+                self.clock.start(id, onTick: cb)
+                Logger().log("starting \(id)");
             }
-            self.core.stopClock = {
-                self.clock.deleteDisplayLink()
+            self.core.stopClock = { id in
+                self.clock.stop(id)
+            }
+            self.core.pauseClock = { paused in
+                self.clock.setPaused(paused)
             }
         }
         .onDisappear {
             self.core.startClock = nil
             self.core.stopClock =  nil
+            self.core.pauseClock = nil
         }
             .ignoresSafeArea(.all)
             .statusBarHidden(true)
@@ -14,15 +14,24 @@ class Core: ObservableObject {
     @State var defaults: UserDefaults = UserDefaults()
     
     var startClock: ((
+        UInt64,
         @escaping(Double?) -> Void
     ) -> Void)?
     
-    var stopClock: (() -> Void)?
+    var stopClock: ((UInt64) -> Void)?
+
+    var pauseClock: ((Bool) -> Void)?
     
 
     init() {
         self.view = try! .bincodeDeserialize(input: [UInt8](RedSiren.view()))
         logInit()
+        playback.onCapture = { data in
+            DispatchQueue.main.async {
+                let capture: CaptureOutput = try! .bincodeDeserialize(input: [UInt8](data))
+                self.update(Event.capture(capture))
+            }
+        }
     }
 
     func update(_ event: Event) {
@@ -82,8 +91,8 @@ class Core: ObservableObject {
                 }
             }
             break
-        case .animate(.start):
-            self.startClock!({ ts in
+        case .animate(.start(let id)):
+            self.startClock!(id, { ts in
                 var data = try! AnimateOperationOutput.done.bincodeSerialize()
                 if let ts = ts {
                     data = try! AnimateOperationOutput.timestamp(ts).bincodeSerialize()
@@ -99,8 +108,14 @@ class Core: ObservableObject {
                 }
             })
             break
-        case .animate(.stop):
-            self.stopClock!()
+        case .animate(.stop(let id)):
+            self.stopClock!(id)
+            break
+        case .animate(.pause):
+            self.pauseClock!(true)
+            break
+        case .animate(.resume):
+            self.pauseClock!(false)
             break
         }
 
@@ -19,8 +19,6 @@ struct IntroView: View {
     var vm: IntroVM
     var ev: (IntroEV) -> Void
 
-    @Environment(\.accessibilityReduceMotion) var reduceMotion
-
     var hSize: CGFloat
     var vSize: CGFloat
 
@@ -61,7 +59,6 @@ struct IntroView: View {
 
                 MenuGroup(vSize: self.vSize, hSize: self.hSize, position: self.vm.layout.menu_position, flip: self.vm.menu_flip).opacity(self.vm.menu_opacity)
             }.onAppear {
-                self.ev(.setReducedMotion(self.reduceMotion))
                 self.ev(.start)
                 Logger().log("start animation")
             }
@@ -89,6 +86,8 @@ struct MenuGroup: View {
             self.positionRect = r
         case .bottomLeft(let r):
             self.positionRect = r
+        case .bottomRight(let r):
+            self.positionRect = r
         case .center(let r):
             self.positionRect = r
         }
@@ -16,6 +16,8 @@ class Playback: NSObject, ObservableObject {
     private var session: AVAudioSession?
     private var auCore: AuCoreBridge?
 
+    var onCapture: ((_ data: Data) -> Void)?
+
     override init() {
 
     }
@@ -43,6 +45,14 @@ class Playback: NSObject, ObservableObject {
                 return
             }
             auCore = auNew()
+            let captures = auCapture(self.auCore!)
+            Task {
+                while let data = await auReceive(captures) {
+                    self.onCapture?(data)
+                }
+
+                Logger().log("playback capture task complete")
+            }
             do {
                 let opData = try op.bincodeSerialize()
                 let rcv = auRequest(self.auCore!, Data.init(opData))
@@ -23,6 +23,8 @@ struct RedCardView<C: View>: View {
             self.rect = r
         case .bottomLeft(let r):
             self.rect = r
+        case .bottomRight(let r):
+            self.rect = r
         case .center(let r):
             self.rect = r
         }
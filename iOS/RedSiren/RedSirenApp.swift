@@ -41,12 +41,15 @@ struct iOSApp: App {
     
     init() {
         self.core = Core()
-        self.core.update(Event.start)
+        self.core.update(Event.start(reduced_motion: UIAccessibility.isReduceMotionEnabled))
     }
     
     var body: some Scene {
         WindowGroup {
             ContentView(core: self.core).environment(\.coreEnv, CoreEnvProvider(core: self.core))
+                .onOpenURL { url in
+                    self.core.update(Event.deepLink(url.absoluteString))
+                }
         }
     }
 }
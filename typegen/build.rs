@@ -7,10 +7,20 @@ fn main() -> anyhow::Result<()> {
     println!("cargo:rerun-if-changed=../aucore");
 
     {
-        use app_core::instrument::{Config, Node};
+        use app_core::instrument::{
+            Config, Decimation, F0Mode, Handedness, Node, Orientation, Rotation, TuningSystem,
+            Voice,
+        };
         use aucore::RedSirenAU;
 
         let mut gen = TypeGen::new();
+        gen.register_type::<Voice>()?;
+        gen.register_type::<Decimation>()?;
+        gen.register_type::<TuningSystem>()?;
+        gen.register_type::<F0Mode>()?;
+        gen.register_type::<Rotation>()?;
+        gen.register_type::<Orientation>()?;
+        gen.register_type::<Handedness>()?;
         gen.register_type::<Config>()?;
         gen.register_type::<Node>()?;
         gen.register_app::<RedSirenAU>()?;
@@ -29,10 +39,15 @@ fn main() -> anyhow::Result<()> {
 
     {
         use app_core::{
+            a11y::{A11yAction, A11yEV, A11yRole, A11yTarget},
+            animate::ClockEV,
             geometry::{Line, Rect},
-            instrument::{layout::MenuPosition, Config, InstrumentEV, Layout, Node, PlaybackEV},
+            instrument::{
+                layout::MenuPosition, Config, Decimation, F0Mode, Handedness, InstrumentEV, Layout,
+                Node, Orientation, PlaybackEV, Rotation, TuningSystem, Voice,
+            },
             intro::IntroEV,
-            play::CaptureOutput,
+            play::{CaptureOutput, Effects, Level, MasterBus, NodeLevel, NodeMetering, Stream},
             tuner::{TriggerState, TunerEV},
             Activity, RedSiren,
         };
@@ -43,22 +58,45 @@ fn main() -> anyhow::Result<()> {
         gen.register_type::<TunerEV>()?;
         gen.register_type::<PlaybackEV>()?;
         gen.register_type::<TriggerState>()?;
+        gen.register_type::<A11yEV>()?;
+        gen.register_type::<A11yTarget>()?;
+        gen.register_type::<A11yRole>()?;
+        gen.register_type::<A11yAction>()?;
+        gen.register_type::<ClockEV>()?;
         gen.register_type_with_samples(vec![
             CaptureOutput::CaptureFFT(vec![(0.0, 0.0)]),
             CaptureOutput::CaptureData(vec![0.0]),
             CaptureOutput::CaptureFFT((0..64).map(|i| (i as f32, (i * 2) as f32 / 1.0)).collect()),
             CaptureOutput::CaptureData((0..64).map(|i| i as f32 / 1.0).collect()),
-            CaptureOutput::CaptureNodesData(
+            CaptureOutput::CaptureLevels(vec![Level::default(), Level::default()]),
+            CaptureOutput::NodeLevels(
                 (1..=5)
-                    .map(|f| (f, (0..64).map(|i| i as f32 / 1.0).collect::<Vec<_>>()))
-                    .collect::<Vec<_>>(),
+                    .map(|f_n| NodeLevel {
+                        f_n,
+                        ..Default::default()
+                    })
+                    .collect(),
             ),
+            CaptureOutput::Spectrum(vec![(0.0, 0.0)]),
         ])?;
+        gen.register_type::<Level>()?;
+        gen.register_type::<MasterBus>()?;
+        gen.register_type::<Effects>()?;
+        gen.register_type::<NodeLevel>()?;
+        gen.register_type::<NodeMetering>()?;
+        gen.register_type::<Stream>()?;
 
         gen.register_type::<Activity>()?;
         gen.register_type::<MenuPosition>()?;
         gen.register_type::<Line>()?;
         gen.register_type::<Rect>()?;
+        gen.register_type::<Voice>()?;
+        gen.register_type::<Decimation>()?;
+        gen.register_type::<TuningSystem>()?;
+        gen.register_type::<F0Mode>()?;
+        gen.register_type::<Rotation>()?;
+        gen.register_type::<Orientation>()?;
+        gen.register_type::<Handedness>()?;
         gen.register_type::<Config>()?;
         gen.register_type::<Layout>()?;
         gen.register_type::<Node>()?;
@@ -1,12 +1,12 @@
 use cfg_if::cfg_if;
-use futures::channel::mpsc::Sender;
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
 use leptos_use::{
-    use_event_listener, use_timestamp_with_controls_and_options, use_window, UseTimestampOptions,
-    UseTimestampReturn,
+    use_event_listener, use_media_query, use_timestamp_with_controls_and_options, use_window,
+    UseTimestampOptions, UseTimestampReturn,
 };
+use std::collections::HashMap;
 
 
 use crate::{
@@ -14,6 +14,7 @@ use crate::{
     util::use_dpi,
 };
 
+mod a11y;
 mod about;
 mod core_bindings;
 mod instrument;
@@ -103,7 +104,10 @@ fn RedSirenCore() -> impl IntoView {
     let view_rw_signal = create_rw_signal(core.view());
     let render = view_rw_signal.write_only();
     let playback = use_context::<ReadSignal<playback::Playback>>().unwrap();
-    let (event, set_event) = create_signal(app_core::Event::Start);
+    let reduced_motion = use_media_query("(prefers-reduced-motion)");
+    let (event, set_event) = create_signal(app_core::Event::Start {
+        reduced_motion: reduced_motion.get_untracked(),
+    });
 
     create_effect(move|_| {
         let pb = playback();
@@ -119,26 +123,65 @@ fn RedSirenCore() -> impl IntoView {
         resume,
         ..
     } = use_timestamp_with_controls_and_options(UseTimestampOptions::default().immediate(false));
-    let (animate_send, set_animate_send) = create_signal(None);
-    let animate_cb = Callback::new(move |sender: Option<Sender<f64>>| {
-        if let Some(sender) = sender {
-            set_animate_send(Some(sender));
+    let (animate_send, set_animate_send) = create_signal(HashMap::new());
+    let animate_cb = Callback::new(move |control| match control {
+        core_bindings::AnimateControl::Start(id, sender) => {
+            set_animate_send.update(|senders| {
+                senders.insert(id, sender);
+            });
             resume();
-            log::debug!("timestamp animation resumed");
-        } else {
-            set_animate_send(None);
+            log::debug!("timestamp animation {id} started");
+        }
+        core_bindings::AnimateControl::Stop(id) => {
+            set_animate_send.update(|senders| {
+                senders.remove(&id);
+            });
+            if animate_send.with_untracked(HashMap::is_empty) {
+                pause();
+            }
+            log::debug!("timestamp animation {id} stopped");
+        }
+        core_bindings::AnimateControl::Pause => {
             pause();
             log::debug!("timestamp animation paused");
         }
+        core_bindings::AnimateControl::Resume => {
+            if !animate_send.with_untracked(HashMap::is_empty) {
+                resume();
+                log::debug!("timestamp animation resumed");
+            }
+        }
     });
 
+    #[cfg(feature = "browser")]
+    {
+        use leptos_use::use_document_visibility;
+
+        let visibility = use_document_visibility();
+        create_effect(move |last| {
+            let hidden = visibility.get() == web_sys::VisibilityState::Hidden;
+            if last.is_some_and(|last| last != hidden) {
+                set_event(app_core::Event::Clock(if hidden {
+                    app_core::animate::ClockEV::Pause
+                } else {
+                    app_core::animate::ClockEV::Resume
+                }));
+            }
+            hidden
+        });
+    }
+
     create_effect(move |last| {
         let ts = timestamp.get();
 
         if last != Some(ts) {
-            if let Some(sender) = animate_send().as_mut() {
-                sender.try_send(ts).expect("send ts");
-            }
+            // skips a frame for a busy animation, forgets a finished one
+            set_animate_send.update_untracked(|senders| {
+                senders.retain(|_, sender| match sender.try_send(ts) {
+                    Ok(()) => true,
+                    Err(e) => !e.is_disconnected(),
+                });
+            });
         }
 
         ts
@@ -157,15 +200,21 @@ fn RedSirenCore() -> impl IntoView {
 
     let location = leptos_router::use_location();
 
-    create_effect(move |_| {
+    create_effect(move |last: Option<()>| {
         let pathname = (location.pathname)();
         log::debug!("browser or user activated pathname: {pathname}");
-        match pathname.as_str() {
-            "/tune" => set_event(app_core::Event::ReflectActivity(app_core::Activity::Tune)),
-            "/play" => set_event(app_core::Event::ReflectActivity(app_core::Activity::Play)),
-            "/listen" => set_event(app_core::Event::ReflectActivity(app_core::Activity::Listen)),
-            "/about" => set_event(app_core::Event::ReflectActivity(app_core::Activity::About)),
-            _ => set_event(app_core::Event::ReflectActivity(app_core::Activity::Intro)),
+        if last.is_none() {
+            let search = location.search.get_untracked();
+            let link = match search.trim_start_matches('?') {
+                "" => pathname,
+                query => format!("{pathname}?{query}"),
+            };
+            set_event(app_core::Event::DeepLink(link));
+        } else {
+            // browser back and forward arrive unasked, the core history
+            // records them as its own steps
+            let activity = app_core::Activity::from_path(&pathname).unwrap_or_default();
+            set_event(app_core::Event::ReflectActivity(activity));
         }
     });
 
@@ -202,6 +251,8 @@ fn RedSirenCore() -> impl IntoView {
     let instrument_ev = SignalSetter::map(move |ev| set_event.set(app_core::Event::InstrumentEvent(ev)));
     let tuner_vm = create_read_slice(view_rw_signal, move |v| v.tuner.clone());
     let tuner_ev = SignalSetter::map(move |ev| set_event.set(app_core::Event::TunerEvent(ev)));
+    let a11y_vm = create_read_slice(view_rw_signal, move |v| v.a11y.clone());
+    let a11y_ev = SignalSetter::map(move |ev| set_event.set(app_core::Event::A11y(ev)));
 
     let view_box = Signal::derive(move || {
         let vb = view_rw_signal.get().view_box;
@@ -244,5 +295,6 @@ fn RedSirenCore() -> impl IntoView {
                 />
             } />
         </Routes>
+        <a11y::A11yComponent vm=a11y_vm ev=a11y_ev />
     }
 }
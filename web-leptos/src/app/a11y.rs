@@ -0,0 +1,120 @@
+use leptos::{ev::KeyboardEvent, *};
+
+use app_core::a11y::{A11yAction, A11yEV, A11yRole, A11yTarget, A11yTree};
+
+fn dom_id(target: &A11yTarget) -> String {
+    match target {
+        A11yTarget::Menu(act) => format!("a11y-menu-{act:?}").to_lowercase(),
+        A11yTarget::Node(f_n) => format!("a11y-node-{f_n}"),
+        A11yTarget::Pair(f_n) => format!("a11y-pair-{f_n}"),
+    }
+}
+
+/// Moves focus with the arrow keys, sliders take up and down for
+/// their pitch, space or enter activates.
+fn key_event(key: &str, role: Option<A11yRole>) -> Option<A11yEV> {
+    let slider = role == Some(A11yRole::Slider);
+    let ev = match key {
+        "ArrowUp" if slider => A11yEV::Act(A11yAction::Raise),
+        "ArrowDown" if slider => A11yEV::Act(A11yAction::Lower),
+        "ArrowRight" | "ArrowDown" => A11yEV::FocusNext,
+        "ArrowLeft" | "ArrowUp" => A11yEV::FocusPrevious,
+        " " | "Enter" => A11yEV::Act(A11yAction::Activate),
+        "s" => A11yEV::Act(A11yAction::Solo),
+        "+" | "=" => A11yEV::Act(A11yAction::Increase),
+        "-" => A11yEV::Act(A11yAction::Decrease),
+        _ => return None,
+    };
+    Some(ev)
+}
+
+#[component]
+fn A11yElement(
+    target: A11yTarget,
+    vm: Signal<A11yTree>,
+    ev: SignalSetter<A11yEV>,
+) -> impl IntoView {
+    let node = move || vm.with(|tree| tree.get(&target).cloned());
+    let focused = create_memo(move |_| vm.with(|tree| tree.focus == Some(target)));
+    let tabbable = move || {
+        vm.with(|tree| match tree.focus {
+            Some(focus) => focus == target,
+            None => tree.nodes.first().is_some_and(|n| n.target == target),
+        })
+    };
+
+    let node_ref = create_node_ref::<html::Div>();
+    create_effect(move |_| {
+        if focused() {
+            if let Some(el) = node_ref.get() {
+                _ = el.focus();
+            }
+        }
+    });
+
+    let style = move || {
+        let rect = node().map(|n| n.rect).unwrap_or_default();
+        format!(
+            r#"
+            width: {}px;
+            height: {}px;
+            top: {}px;
+            left: {}px;
+            "#,
+            rect.width(),
+            rect.height(),
+            rect.top_left().y,
+            rect.top_left().x,
+        )
+    };
+    let role = move || match node().map(|n| n.role) {
+        Some(A11yRole::Slider) => "slider",
+        _ => "button",
+    };
+
+    view! {
+        <div
+            id=dom_id(&target)
+            node_ref=node_ref
+            class="absolute rounded-3xl focus:outline focus:outline-4 focus:outline-cinnabar"
+            style=style
+            role=role
+            aria-label=move || node().map(|n| n.label)
+            aria-pressed=move || node().and_then(|n| n.pressed).map(|p| p.to_string())
+            tabindex=move || if tabbable() { "0" } else { "-1" }
+            on:focus=move |_| ev.set(A11yEV::Focus(Some(target)))
+        />
+    }
+}
+
+/// Focusable elements over the screen for keyboards and screen readers,
+/// they leave pointer input to the views under them.
+#[component]
+pub fn A11yComponent(
+    #[prop(into)] vm: Signal<A11yTree>,
+    ev: SignalSetter<A11yEV>,
+) -> impl IntoView {
+    let on_keydown = move |e: KeyboardEvent| {
+        let focused = vm.with(|tree| tree.focused().map(|n| (n.role, n.actions.clone())));
+        let Some(a11y_ev) = key_event(&e.key(), focused.as_ref().map(|(role, _)| *role)) else {
+            return;
+        };
+        if let A11yEV::Act(action) = a11y_ev {
+            if !focused.is_some_and(|(_, actions)| actions.contains(&action)) {
+                return;
+            }
+        }
+        e.prevent_default();
+        ev.set(a11y_ev);
+    };
+
+    view! {
+        <div class="a11y absolute top-0 left-0 w-full h-full pointer-events-none" on:keydown=on_keydown>
+            <For
+                each=move || vm().nodes
+                key=|node| dom_id(&node.target)
+                children=move |node| view! { <A11yElement target=node.target vm=vm ev=ev /> }
+            />
+        </div>
+    }
+}
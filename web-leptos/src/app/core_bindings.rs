@@ -8,13 +8,22 @@ use leptos::*;
 
 use app_core::animate::{AnimateOperation, AnimateOperationOutput};
 use app_core::{
-    navigate::NavigateOperation, Activity, Effect, Event, RedSiren, RedSirenCapabilities, ViewModel,
+    navigate::NavigateOperation, Effect, Event, RedSiren, RedSirenCapabilities, ViewModel,
 };
 
 use super::playback;
 
 pub type Core = Rc<app_core::Core<Effect, RedSiren>>;
 
+/// What the shell does with its frame clock, animations by id.
+#[derive(Clone)]
+pub enum AnimateControl {
+    Start(usize, Sender<f64>),
+    Stop(usize),
+    Pause,
+    Resume,
+}
+
 pub fn new() -> Core {
     Rc::new(app_core::Core::new::<RedSirenCapabilities>())
 }
@@ -25,7 +34,7 @@ pub fn update(
     render: WriteSignal<ViewModel>,
     playback: playback::Playback,
     navigate: Callback<&str>,
-    animate_cb: Callback<Option<Sender<f64>>>,
+    animate_cb: Callback<AnimateControl>,
 ) {
     for effect in core.process_event(event) {
         process_effect(core, effect, render, playback.clone(), navigate, animate_cb);
@@ -39,7 +48,7 @@ pub fn process_effect(
     render: WriteSignal<ViewModel>,
     playback: playback::Playback,
     navigate: Callback<&str>,
-    animate_cb: Callback<Option<Sender<f64>>>,
+    animate_cb: Callback<AnimateControl>,
 ) {
     match effect {
         Effect::Render(_) => {
@@ -76,15 +85,7 @@ pub fn process_effect(
         }
         Effect::Navigate(nav) => match nav.operation {
             NavigateOperation::To(activity) => {
-                let path = match activity {
-                    Activity::Intro => "/",
-                    Activity::Tune => "/tune",
-                    Activity::Play => "/play",
-                    Activity::Listen => "/listen",
-                    Activity::About => "/about",
-                };
-
-                navigate(path);
+                navigate(activity.path());
 
                 update(
                     core,
@@ -122,7 +123,7 @@ pub fn process_effect(
             }
         }
         Effect::Animate(mut req) => match req.operation {
-            AnimateOperation::Start => {
+            AnimateOperation::Start { id } => {
                 let (sx, mut rx) = channel::<f64>(1);
                 let core = core.clone();
                 let playback = playback.clone();
@@ -155,9 +156,11 @@ pub fn process_effect(
                     log::debug!("receive ts ended");
                 });
 
-                animate_cb(Some(sx));
+                animate_cb(AnimateControl::Start(id, sx));
             }
-            AnimateOperation::Stop => animate_cb(None),
+            AnimateOperation::Stop { id } => animate_cb(AnimateControl::Stop(id)),
+            AnimateOperation::Pause => animate_cb(AnimateControl::Pause),
+            AnimateOperation::Resume => animate_cb(AnimateControl::Resume),
         },
     };
 }
@@ -3,13 +3,14 @@ use leptos_meta::Title;
 
 use app_core::instrument;
 pub use button::ButtonComponent;
-use leptos_use::{use_raf_fn_with_options, utils::Pausable, UseRafFnOptions};
+pub use meter::MeterComponent;
 pub use string::StringComponent;
 pub use track::TrackComponent;
 
 use super::menu::MenuComponent;
 
 mod button;
+mod meter;
 mod string;
 mod track;
 
@@ -26,41 +27,8 @@ pub fn InstrumentComponent(
 
     let playing = Signal::derive(move || vm().playing);
 
-    let (ts, set_ts) = create_signal(0.0);
-    let Pausable {
-        resume,
-        pause,
-        is_active,
-    } = use_raf_fn_with_options(
-        move |args| {
-          set_ts(args.timestamp);
-        },
-        UseRafFnOptions::default().immediate(false),
-    );
-
-    create_effect(move |prev| {
-      let now = ts();
-      if prev.map_or(true, |p| now - p >= 42.0 ) {
-        ev(instrument::InstrumentEV::RequestSnoops);
-        now
-      }
-      else {
-        log::warn!("skip ts: {prev:?}");
-        prev.unwrap_or(0.0)
-      }
-    });
-
-
-
-    create_effect(move |_| {
-        if playing() {
-            resume()
-        } else if is_active() {
-            pause()
-        }
-    });
-
     let menu_position = Signal::derive(move || vm().layout.menu_position);
+    let levels = Signal::derive(move || vm().levels);
 
     let buttons = move || {
         vm().layout
@@ -99,6 +67,7 @@ pub fn InstrumentComponent(
         <div class="w-full h-full relative">
           {buttons}
         </div>
+        <MeterComponent levels=levels />
         <MenuComponent position={menu_position} playing=playing />
       </div>
     }
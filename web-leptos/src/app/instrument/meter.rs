@@ -0,0 +1,35 @@
+use leptos::*;
+
+use app_core::play::Level;
+
+const FLOOR_DB: f32 = -60.0;
+
+fn level_height(value: f32) -> f32 {
+    let db = 20.0 * value.max(f32::EPSILON).log10();
+    ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0) * 100.0
+}
+
+#[component]
+pub fn MeterComponent(#[prop(into)] levels: Signal<Vec<Level>>) -> impl IntoView {
+    let channels = move || {
+        levels()
+            .into_iter()
+            .map(|level| {
+                let rms = format!("height: {}%;", level_height(level.rms));
+                let peak = format!("bottom: {}%;", level_height(level.peak));
+                view! {
+                  <div class="relative w-1 h-full bg-black dark:bg-red opacity-25">
+                    <div class="absolute bottom-0 w-full bg-black dark:bg-red" style=rms />
+                    <div class="absolute w-full h-px bg-black dark:bg-red" style=peak />
+                  </div>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+      <div class="absolute top-0 right-0 h-16 m-2 flex gap-1 meter">
+        {channels}
+      </div>
+    }
+}
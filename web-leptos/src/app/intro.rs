@@ -1,6 +1,5 @@
 use leptos::*;
 use leptos_meta::Title;
-use leptos_use::use_media_query;
 
 use app_core::app::intro;
 
@@ -12,15 +11,11 @@ pub fn IntroComponent(
     vm: Signal<intro::IntroVM>,
     ev: SignalSetter<intro::IntroEV>,
 ) -> impl IntoView {
-    let reduced_motion = use_media_query("(prefers-reduced-motion)");
-
-    create_effect(move |_| {
-        let reduced_motion = reduced_motion.get();
-        ev.set(intro::IntroEV::SetReducedMotion(reduced_motion));
-    });
-
     create_effect(move |_| ev.set(intro::IntroEV::Start));
 
+    #[cfg(all(feature = "browser", debug_assertions))]
+    create_effect(move |_| load_timeline(ev));
+
     let view_box = move || {
         let vb = vm().view_box;
         format!(
@@ -110,6 +105,48 @@ pub fn IntroComponent(
     }
 }
 
+/// Loads `timeline.json` from the site assets in dev builds, the intro
+/// choreography can change without rebuilding the core.
+///
+/// `public/timeline.json` starts as a copy of the core's built in one.
+/// Responses that aren't JSON, like an index page served for a missing
+/// file, keep the built in timeline.
+#[cfg(all(feature = "browser", debug_assertions))]
+fn load_timeline(ev: SignalSetter<intro::IntroEV>) {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::{spawn_local, JsFuture};
+
+    spawn_local(async move {
+        let window = web_sys::window().expect("window");
+        let Ok(response) = JsFuture::from(window.fetch_with_str("/timeline.json")).await else {
+            return;
+        };
+        let response = response.unchecked_into::<web_sys::Response>();
+        if !response.ok() {
+            log::debug!("no timeline.json, keeping the built in timeline");
+            return;
+        }
+        let is_json = response
+            .headers()
+            .get("content-type")
+            .ok()
+            .flatten()
+            .is_some_and(|content_type| content_type.contains("json"));
+        if !is_json {
+            log::debug!("timeline.json is not json, keeping the built in timeline");
+            return;
+        }
+
+        let json = match response.text() {
+            Ok(text) => JsFuture::from(text).await.ok().and_then(|t| t.as_string()),
+            Err(_) => None,
+        };
+        if let Some(json) = json {
+            ev.set(intro::IntroEV::LoadTimeline(json));
+        }
+    });
+}
+
 #[component]
 pub fn SplashPicture() -> impl IntoView {
     view! {
@@ -33,6 +33,7 @@ pub fn RedCardComponent(
             MenuPosition::TopLeft(_) => "top-left",
             MenuPosition::TopRight(_) => "top-right",
             MenuPosition::BottomLeft(_) => "bottom-left",
+            MenuPosition::BottomRight(_) => "bottom-right",
             MenuPosition::Center(_) => "center",
         };
 